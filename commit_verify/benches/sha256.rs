@@ -0,0 +1,73 @@
+// Client-side-validation foundation libraries.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Throughput of the SHA256 backend used by the commitments. Run it with and
+//! without `fast-hash` feature to compare the backends:
+//!
+//! ```console
+//! cargo bench -p commit_verify --bench sha256
+//! cargo bench -p commit_verify --bench sha256 --features fast-hash
+//! ```
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use amplify::confinement::LargeVec;
+use commit_verify::mpc::{Leaf, Message, ProtocolId};
+use commit_verify::{DigestExt, MerkleHash, Sha256};
+
+const ROUNDS: u32 = 16;
+
+fn measure(mut f: impl FnMut()) -> Duration {
+    f();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn main() {
+    let backend = if cfg!(feature = "fast-hash") {
+        "asm"
+    } else {
+        "soft"
+    };
+    println!("sha256 backend: {backend}");
+
+    let data = vec![0xA5u8; 16 * 1024 * 1024];
+    let elapsed = measure(|| {
+        let mut engine = Sha256::default();
+        engine.input_raw(black_box(&data));
+        black_box(engine.finish());
+    });
+    let throughput = data.len() as f64 / elapsed.as_secs_f64() / 1024.0 / 1024.0;
+    println!("sha256 of 16 MiB:        {elapsed:?} ({throughput:.1} MiB/s)");
+
+    let leaves = LargeVec::try_from_iter((0..0x10000u32).map(|no| {
+        Leaf::inhabited(ProtocolId::from([no as u8; 32]), Message::from([(no >> 8) as u8; 32]))
+    }))
+    .expect("less than 2^32 leaves");
+    let elapsed = measure(|| {
+        black_box(MerkleHash::merklize(black_box(&leaves)));
+    });
+    println!("merklize of 2^16 leaves: {elapsed:?}");
+}
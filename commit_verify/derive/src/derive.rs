@@ -19,8 +19,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use proc_macro2::TokenStream as TokenStream2;
-use syn::Result;
+use amplify_syn::{DataInner, Fields};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{Error, Index, Result};
 
 use crate::params::{CommitDerive, StrategyAttr};
 
@@ -47,8 +48,33 @@ impl CommitDerive {
                 use amplify::Wrapper;
                 engine.commit_to_merkle(self.as_inner().merklize());
             },
+            StrategyAttr::Unordered => {
+                let fields = self.derive_fields()?;
+                quote! {
+                    let fields = vec![
+                        #( #trait_crate::CommitEngine::field_commitment(&self.#fields) ),*
+                    ];
+                    engine.commit_to_unordered::<Self>(fields);
+                }
+            }
         };
 
+        let id_impl = self.conf.tag.as_ref().map(|tag| {
+            quote! {
+                #[automatically_derived]
+                impl #trait_crate::CommitmentId for #commitment_id {
+                    const TAG: &'static str = #tag;
+                }
+
+                #[automatically_derived]
+                impl From<#trait_crate::Sha256> for #commitment_id {
+                    fn from(hasher: #trait_crate::Sha256) -> Self {
+                        <#trait_crate::Sha256 as #trait_crate::DigestExt>::finish(hasher).into()
+                    }
+                }
+            }
+        });
+
         Ok(quote! {
             #[automatically_derived]
             impl #impl_generics #trait_crate::CommitEncode for #ident_name #ty_generics #where_clause {
@@ -58,6 +84,33 @@ impl CommitDerive {
                     #inner
                 }
             }
+
+            #id_impl
         })
     }
+
+    fn derive_fields(&self) -> Result<Vec<TokenStream2>> {
+        match &self.data.inner {
+            DataInner::Struct(Fields::Named(fields)) => Ok(fields
+                .iter()
+                .map(|field| {
+                    let name = &field.name;
+                    quote! { #name }
+                })
+                .collect()),
+            DataInner::Struct(Fields::Unnamed(fields)) => Ok(fields
+                .iter()
+                .enumerate()
+                .map(|(index, _)| {
+                    let index = Index::from(index);
+                    quote! { #index }
+                })
+                .collect()),
+            _ => Err(Error::new(
+                Span::call_site(),
+                "`unordered` commitment encoding strategy can be used only with structures having \
+                 fields",
+            )),
+        }
+    }
 }
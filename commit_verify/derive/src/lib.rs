@@ -44,6 +44,16 @@
 //!
 //! Derivation macros accept `#[commit_encoding()]` attribute with the following
 //! arguments:
+//!
+//! ### `strategy = unordered`
+//!
+//! Commits to the structure independently from the order of its fields, by
+//! sorting the commitments of the individual fields. Each field commitment is
+//! produced by the `CommitEncode` implementation of the field type, thus
+//! **all fields must have types implementing `CommitEncode`**. Primitive types
+//! (like `u8`, `u64` or `String`) don't implement it; such fields must be
+//! wrapped into newtypes deriving `CommitEncode` (for instance, with
+//! `strategy = strict`). Fields of primitive types are rejected by the macro.
 
 #[macro_use]
 extern crate quote;
@@ -19,24 +19,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use amplify_syn::{ArgValueReq, AttrReq, DataType, ParametrizedAttr, TypeClass};
+use amplify_syn::{ArgValueReq, AttrReq, DataType, LiteralClass, ParametrizedAttr, TypeClass};
 use proc_macro2::Span;
 use quote::ToTokens;
-use syn::{DeriveInput, Error, Path, Result};
+use syn::{Data, DeriveInput, Error, LitStr, Path, Result, Type};
 
 const ATTR: &str = "commit_encode";
 const ATTR_CRATE: &str = "crate";
 const ATTR_ID: &str = "id";
+const ATTR_TAG: &str = "tag";
 const ATTR_STRATEGY: &str = "strategy";
 const ATTR_STRATEGY_STRICT: &str = "strict";
 const ATTR_STRATEGY_CONCEAL: &str = "conceal";
 const ATTR_STRATEGY_TRANSPARENT: &str = "transparent";
 const ATTR_STRATEGY_MERKLIZE: &str = "merklize";
+const ATTR_STRATEGY_UNORDERED: &str = "unordered";
+
+/// Primitive types which don't implement `CommitEncode`.
+const PRIMITIVE_TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "bool",
+    "char", "f32", "f64", "str", "String",
+];
 
 pub struct ContainerAttr {
     pub commit_crate: Path,
     pub strategy: StrategyAttr,
     pub id: Path,
+    pub tag: Option<LitStr>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -45,6 +54,7 @@ pub enum StrategyAttr {
     ConcealStrict,
     Transparent,
     Merklize,
+    Unordered,
 }
 
 impl TryFrom<&Path> for StrategyAttr {
@@ -56,12 +66,14 @@ impl TryFrom<&Path> for StrategyAttr {
             ATTR_STRATEGY_CONCEAL => Ok(StrategyAttr::ConcealStrict),
             ATTR_STRATEGY_TRANSPARENT => Ok(StrategyAttr::Transparent),
             ATTR_STRATEGY_MERKLIZE => Ok(StrategyAttr::Merklize),
+            ATTR_STRATEGY_UNORDERED => Ok(StrategyAttr::Unordered),
             unknown => Err(Error::new(
                 Span::call_site(),
                 format!(
                     "invalid commitment encoding value for `strategy` attribute `{unknown}`; only \
                      `{ATTR_STRATEGY_TRANSPARENT}`, `{ATTR_STRATEGY_STRICT}`, \
-                     `{ATTR_STRATEGY_CONCEAL}`, or `{ATTR_STRATEGY_MERKLIZE}`  are allowed"
+                     `{ATTR_STRATEGY_CONCEAL}`, `{ATTR_STRATEGY_MERKLIZE}`, or \
+                     `{ATTR_STRATEGY_UNORDERED}` are allowed"
                 ),
             )),
         }
@@ -76,12 +88,14 @@ impl TryFrom<ParametrizedAttr> for ContainerAttr {
             ATTR_CRATE => ArgValueReq::optional(TypeClass::Path),
             ATTR_ID => ArgValueReq::required(TypeClass::Path),
             ATTR_STRATEGY => ArgValueReq::required(TypeClass::Path),
+            ATTR_TAG => ArgValueReq::optional(LiteralClass::Str),
         ]);
         params.check(req)?;
 
         let path = params.arg_value(ATTR_STRATEGY).expect("must be present");
         let strategy = StrategyAttr::try_from(&path)?;
         let id = params.arg_value(ATTR_ID).expect("must be present");
+        let tag = params.arg_value(ATTR_TAG).ok();
 
         Ok(ContainerAttr {
             commit_crate: params
@@ -89,6 +103,7 @@ impl TryFrom<ParametrizedAttr> for ContainerAttr {
                 .unwrap_or_else(|_| path!(commit_verify)),
             strategy,
             id,
+            tag,
         })
     }
 }
@@ -104,7 +119,39 @@ impl TryFrom<DeriveInput> for CommitDerive {
     fn try_from(input: DeriveInput) -> Result<Self> {
         let params = ParametrizedAttr::with(ATTR, &input.attrs)?;
         let conf = ContainerAttr::try_from(params)?;
+        if conf.strategy == StrategyAttr::Unordered {
+            check_unordered_fields(&input)?;
+        }
         let data = DataType::with(input, ident!(commit_encode))?;
         Ok(Self { data, conf })
     }
 }
+
+/// Checks that the structure using `unordered` strategy has no fields of the
+/// primitive types, since each field must be commit-encoded on its own.
+fn check_unordered_fields(input: &DeriveInput) -> Result<()> {
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        _ => return Ok(()),
+    };
+    for field in &data.fields {
+        let ident = match &field.ty {
+            Type::Path(path) => path.path.get_ident(),
+            _ => None,
+        };
+        match ident {
+            Some(ident) if PRIMITIVE_TYPES.contains(&ident.to_string().as_str()) => {
+                return Err(Error::new_spanned(
+                    &field.ty,
+                    format!(
+                        "`{ATTR_STRATEGY_UNORDERED}` commitment encoding strategy requires all \
+                         fields to implement `CommitEncode`, which is not the case for `{ident}`; \
+                         wrap the field into a newtype deriving `CommitEncode`"
+                    ),
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
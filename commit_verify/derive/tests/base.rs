@@ -228,6 +228,143 @@ fn conceal() -> common::Result {
     Ok(())
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = TEST_LIB)]
+#[derive(CommitEncode)]
+#[commit_encode(strategy = strict, id = DumbId)]
+struct Short(u16);
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = TEST_LIB)]
+#[derive(CommitEncode)]
+#[commit_encode(strategy = strict, id = DumbId)]
+struct Long(u64);
+
+#[test]
+fn strategy_unordered() -> common::Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = unordered, id = DumbId)]
+    struct Forward {
+        a: Short,
+        b: Long,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = unordered, id = DumbId)]
+    struct Backward {
+        b: Long,
+        a: Short,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = unordered, id = DumbId)]
+    struct Tuple(Long, Short);
+
+    let forward = Forward {
+        a: Short(0xdead),
+        b: Long(0xbeefcafebaddafec),
+    };
+    verify_commit(
+        forward.clone(),
+        "41c9d4ad04615bf7272709138c21812dd050d137308a758567d555aa21448cb0",
+    );
+    let backward = Backward {
+        b: Long(0xbeefcafebaddafec),
+        a: Short(0xdead),
+    };
+    let tuple = Tuple(Long(0xbeefcafebaddafec), Short(0xdead));
+    assert_eq!(forward.commit_id(), backward.commit_id());
+    assert_eq!(forward.commit_id(), tuple.commit_id());
+
+    let other = Forward {
+        a: Short(0xdead),
+        b: Long(0xbeefcafebaddafed),
+    };
+    assert_ne!(forward.commit_id(), other.commit_id());
+
+    Ok(())
+}
+
+#[test]
+fn strategy_unordered_value_swap() -> common::Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = unordered, id = DumbId)]
+    struct Pair {
+        a: Short,
+        b: Short,
+    }
+
+    // Values of the fields of the same type can be swapped without changing
+    // the commitment
+    let pair = Pair {
+        a: Short(1),
+        b: Short(2),
+    };
+    let swapped = Pair {
+        a: Short(2),
+        b: Short(1),
+    };
+    verify_commit(pair.clone(), "e10eaa1d0c4a3d5693f3b45f152026aebe8030f75caca67ba504f485d04e0cfe");
+    assert_eq!(pair.commit_id(), swapped.commit_id());
+
+    verify_commit(
+        Pair {
+            a: Short(1),
+            b: Short(1),
+        },
+        "0a44cf566e7a59a860cc81ae1975df56b31d9148799531ea9dc77103580934da",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn strategy_unordered_primitive() { common::compile_test("compile-fail") }
+
+#[test]
+fn tagged_id() -> common::Result {
+    #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = TEST_LIB)]
+    struct TaggedId(
+        #[from]
+        #[from([u8; 32])]
+        Bytes32,
+    );
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = strict, id = TaggedId, tag = "urn:test:tagged-id#2024-02-10")]
+    struct Tagged {
+        a: u16,
+        b: u8,
+    }
+
+    assert_eq!(TaggedId::TAG, "urn:test:tagged-id#2024-02-10");
+
+    let mut hasher = Sha256::from_tag(TaggedId::TAG);
+    hasher.input_raw(&[0xad, 0xde, 0xef]);
+    assert_eq!(Tagged { a: 0xdead, b: 0xef }.commit_id(), TaggedId::from(hasher));
+
+    Ok(())
+}
+
 /* TODO: Refactor
 #[test]
 fn merklize() -> common::Result {
@@ -0,0 +1,35 @@
+// Client-side-validation foundation libraries.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![allow(dead_code, unused_imports)]
+
+extern crate commit_verify;
+
+use commit_verify::{CommitEncode, StrictHash};
+
+#[derive(CommitEncode)]
+#[commit_encode(strategy = unordered, id = StrictHash)]
+struct Primitive {
+    a: u8, //~ ERROR requires all fields to implement `CommitEncode`
+    b: u64,
+}
+
+fn main() {}
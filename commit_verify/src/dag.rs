@@ -0,0 +1,138 @@
+// Client-side-validation foundation libraries.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commitments to directed acyclic graphs (DAGs) of client-side-validated
+//! data, where each node commits to its own data and to the ids of all of its
+//! parents. Thus, the id of a node changes whenever any of its ancestors
+//! change.
+
+use std::collections::BTreeSet;
+
+use amplify::confinement::{Confined, U16 as U16MAX};
+use amplify::{Bytes32, Wrapper};
+use sha2::Sha256;
+
+use crate::{CommitId, CommitmentId, DigestExt, StrictHash, LIB_NAME_COMMIT_VERIFY};
+
+/// Content-addressed identifier of a [`DagNode`].
+#[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
+#[wrapper(Deref, BorrowSlice, Display, FromStr, Hex, Index, RangeOps)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", transparent)
+)]
+pub struct DagNodeId(
+    #[from]
+    #[from([u8; 32])]
+    Bytes32,
+);
+
+impl CommitmentId for DagNodeId {
+    const TAG: &'static str = "urn:ubideco:dag:node#2024-02-08";
+}
+
+impl From<Sha256> for DagNodeId {
+    fn from(hash: Sha256) -> Self { hash.finish().into() }
+}
+
+/// Node of a directed acyclic graph, committing to the node data and to the
+/// ids of its parents.
+///
+/// Parent ids are kept in a sorted set, such that the order in which parents
+/// are provided doesn't affect the node id.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[derive(CommitEncode)]
+#[commit_encode(crate = crate, strategy = strict, id = DagNodeId)]
+pub struct DagNode {
+    data: StrictHash,
+    parents: Confined<BTreeSet<DagNodeId>, 0, U16MAX>,
+}
+
+impl DagNode {
+    /// Constructs DAG node from the commitment to the node data and the ids of
+    /// the node parents.
+    ///
+    /// # Panics
+    ///
+    /// If the number of parents exceeds `u16::MAX`.
+    pub fn with(data: StrictHash, parents: impl IntoIterator<Item = DagNodeId>) -> Self {
+        Self {
+            data,
+            parents: Confined::try_from_iter(parents).expect("too many DAG node parents"),
+        }
+    }
+
+    /// Returns commitment to the node data.
+    pub fn data(&self) -> StrictHash { self.data }
+
+    /// Returns ids of the node parents.
+    pub fn parents(&self) -> impl Iterator<Item = DagNodeId> + '_ { self.parents.iter().copied() }
+
+    /// Detects whether the node has no parents, i.e. is a root of the DAG.
+    pub fn is_root(&self) -> bool { self.parents.is_empty() }
+
+    /// Computes content-addressed id of the node.
+    pub fn node_id(&self) -> DagNodeId { self.commit_id() }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dag(root_data: u8) -> [DagNodeId; 4] {
+        //   A
+        //  / \
+        // B   C
+        //  \ /
+        //   D
+        let a = DagNode::with(StrictHash::from([root_data; 32]), []).node_id();
+        let b = DagNode::with(StrictHash::from([1; 32]), [a]).node_id();
+        let c = DagNode::with(StrictHash::from([2; 32]), [a]).node_id();
+        let d = DagNode::with(StrictHash::from([3; 32]), [b, c]).node_id();
+        [a, b, c, d]
+    }
+
+    #[test]
+    fn parents_order() {
+        let [a, b, ..] = dag(0);
+        let data = StrictHash::from([0xFF; 32]);
+        assert_eq!(DagNode::with(data, [a, b]).node_id(), DagNode::with(data, [b, a]).node_id());
+        assert!(DagNode::with(data, []).is_root());
+        assert!(!DagNode::with(data, [a]).is_root());
+    }
+
+    #[test]
+    fn ancestor_change() {
+        let ids1 = dag(0);
+        let ids2 = dag(0);
+        assert_eq!(ids1, ids2);
+
+        let ids3 = dag(0xFF);
+        for (id1, id3) in ids1.iter().zip(&ids3) {
+            assert_ne!(id1, id3);
+        }
+    }
+}
@@ -73,3 +73,33 @@ impl DigestExt<20> for Ripemd160 {
 
     fn finish(self) -> [u8; 20] { self.finalize().into() }
 }
+
+#[cfg(test)]
+mod test {
+    use amplify::hex::FromHex;
+
+    use super::*;
+
+    // Test vectors are the same for all sha2 backends, including the one enabled
+    // with `fast-hash` feature.
+    #[test]
+    fn sha256_backend() {
+        let mut engine = Sha256::default();
+        engine.input_raw(b"abc");
+        assert_eq!(
+            engine.finish().to_vec(),
+            Vec::from_hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+                .unwrap()
+        );
+
+        let mut engine = Sha256::default();
+        for _ in 0..1000 {
+            engine.input_raw(&[b'a'; 1000]);
+        }
+        assert_eq!(
+            engine.finish().to_vec(),
+            Vec::from_hex("cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0")
+                .unwrap()
+        );
+    }
+}
@@ -188,6 +188,48 @@ where
     }
 }
 
+impl<Msg, A, B, Protocol> EmbedCommitProof<Msg, (A, B), Protocol> for (A::Proof, B::Proof)
+where
+    A: EmbedCommitVerify<Msg, Protocol>,
+    B: EmbedCommitVerify<Msg, Protocol, CommitError = A::CommitError>,
+    A::Proof: Eq,
+    B::Proof: Eq,
+    Protocol: CommitmentProtocol,
+{
+    fn restore_original_container(
+        &self,
+        commit_container: &(A, B),
+    ) -> Result<(A, B), EmbedVerifyError<A::CommitError>> {
+        let (a, b) = commit_container;
+        Ok((self.0.restore_original_container(a)?, self.1.restore_original_container(b)?))
+    }
+}
+
+/// Embeds the same message into a pair of containers (for instance, two
+/// different transaction outputs), such that the commitment is verifiable only
+/// when both of the containers together with both proofs are revealed.
+///
+/// Verification of a single container from the pair against the message
+/// doesn't prove the presence of the commitment in the other one; thus,
+/// protocols relying on the pair commitment MUST always verify the pair as a
+/// whole.
+impl<Msg, A, B, Protocol> EmbedCommitVerify<Msg, Protocol> for (A, B)
+where
+    A: EmbedCommitVerify<Msg, Protocol>,
+    B: EmbedCommitVerify<Msg, Protocol, CommitError = A::CommitError>,
+    A::Proof: Eq,
+    B::Proof: Eq,
+    Protocol: CommitmentProtocol,
+{
+    type Proof = (A::Proof, B::Proof);
+    type CommitError = A::CommitError;
+
+    fn embed_commit(&mut self, msg: &Msg) -> Result<Self::Proof, Self::CommitError> {
+        let (a, b) = self;
+        Ok((a.embed_commit(msg)?, b.embed_commit(msg)?))
+    }
+}
+
 /// Helpers for writing test functions working with embed-commit-verify scheme.
 #[cfg(test)]
 pub(crate) mod test_helpers {
@@ -384,4 +426,21 @@ mod test {
             DummyVec(small_vec![0xC0; 15]),
         );
     }
+
+    #[test]
+    fn test_embed_commit_pair() {
+        embed_commit_verify_suite::<SmallVec<u8>, (DummyVec, DummyVec)>(
+            gen_messages(),
+            (DummyVec(default!()), DummyVec(small_vec![0xC0; 15])),
+        );
+
+        let msg: SmallVec<u8> = small_vec![0xDE, 0xAD];
+        let mut pair = (DummyVec(default!()), DummyVec(small_vec![0xC0; 15]));
+        let proof = pair.embed_commit(&msg).unwrap();
+        assert!(pair.verify(&msg, &proof).is_ok());
+
+        let (first, _) = pair.clone();
+        let tampered = (first, DummyVec(small_vec![0xC0; 15]));
+        assert!(tampered.verify(&msg, &proof).is_err());
+    }
 }
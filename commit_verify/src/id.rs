@@ -19,13 +19,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::{self, Display, Formatter};
 use std::hash::Hash;
 
 use amplify::confinement::{Confined, TinyVec, U64 as U64MAX};
 use amplify::Bytes32;
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 use strict_encoding::{Sizing, StreamWriter, StrictDumb, StrictEncode, StrictType};
 use strict_types::typesys::TypeFqn;
 
@@ -47,6 +47,7 @@ pub enum CommitStep {
     Hashed(TypeFqn),
     Merklized(TypeFqn),
     Concealed(TypeFqn),
+    Unordered(TypeFqn),
 }
 
 #[derive(Clone, Debug)]
@@ -145,6 +146,40 @@ impl CommitEngine {
         self.inner_commit_to::<_, COMMIT_MAX_LEN>(&concealed);
     }
 
+    /// Computes commitment to a field of some data type for the use in
+    /// [`Self::commit_to_unordered`], using the commit encoding of the field
+    /// type (thus applying its concealment, merklization etc).
+    pub fn field_commitment<T: CommitEncode>(value: &T) -> [u8; 32] {
+        value.commit().finish().finish()
+    }
+
+    /// Commits to the data type `T` consisting of the provided field
+    /// commitments (see [`Self::field_commitment`]) independently from the
+    /// order of the fields.
+    ///
+    /// The field commitments are sorted lexicographically and then hashed.
+    /// Thus, two values having the same set of field commitments produce the
+    /// same commitment, regardless of the field order in the type declaration.
+    ///
+    /// NB: Since the field names and positions are not committed to, the
+    /// values of fields with the same type can be swapped without changing
+    /// the commitment: `{ a: 1, b: 2 }` and `{ a: 2, b: 1 }` commit to the
+    /// same value. The strategy must be used only for the types where the
+    /// fields can't be confused (for instance, because each of them has a
+    /// distinct type with a distinct commitment).
+    pub fn commit_to_unordered<T: StrictType>(&mut self, mut fields: Vec<[u8; 32]>) {
+        debug_assert!(!self.finished);
+        let fqn = commitment_fqn::<T>();
+        self.layout
+            .push(CommitStep::Unordered(fqn))
+            .expect("too many fields for commitment");
+
+        fields.sort();
+        for field in fields {
+            self.hasher.input_raw(&field);
+        }
+    }
+
     pub fn commit_to_list<T, const MIN: usize, const MAX: usize>(
         &mut self,
         collection: &Confined<Vec<T>, MIN, MAX>,
@@ -195,6 +230,24 @@ impl CommitEngine {
         self.inner_commit_to::<_, COMMIT_MAX_LEN>(&collection);
     }
 
+    /// Commits to a hash map in the same way as [`Self::commit_to_map`] does
+    /// for a `BTreeMap`, by ordering the map entries by their keys. Thus, the
+    /// commitment doesn't depend on the order of the map entries insertion,
+    /// and matches the commitment to a `BTreeMap` with the same entries.
+    pub fn commit_to_hash_map<K, V, const MIN: usize, const MAX: usize>(
+        &mut self,
+        collection: &Confined<HashMap<K, V>, MIN, MAX>,
+    ) where
+        K: Ord + Hash + Clone + StrictEncode + StrictDumb,
+        V: Clone + StrictEncode + StrictDumb,
+    {
+        let ordered = Confined::<BTreeMap<K, V>, MIN, MAX>::try_from_iter(
+            collection.iter().map(|(k, v)| (k.clone(), v.clone())),
+        )
+        .expect("same collection size");
+        self.commit_to_map(&ordered);
+    }
+
     pub fn as_layout(&mut self) -> &[CommitStep] {
         self.finished = true;
         self.layout.as_ref()
@@ -237,6 +290,24 @@ impl Display for CommitLayout {
 
 pub trait CommitmentId: Copy + Ord + From<Sha256> + StrictType {
     const TAG: &'static str;
+
+    /// Returns hash of the [`Self::TAG`] string, which is used (twice) as a
+    /// prefix of the tagged hash producing the commitment.
+    fn commitment_tag() -> [u8; 32] { Sha256::digest(Self::TAG.as_bytes()).into() }
+
+    /// Returns [`Self::TAG`] string if it is a URN, i.e. is a human-readable
+    /// tag name starting with `urn:` prefix.
+    fn commitment_urn() -> Option<&'static str> {
+        Self::TAG.starts_with("urn:").then_some(Self::TAG)
+    }
+}
+
+/// Verifies commitment by re-running the commitment procedure over a revealed
+/// data and checking that the result matches the commitment.
+pub trait RevealVerify<Revealed> {
+    /// Re-computes commitment to the `revealed` data and checks that it is
+    /// equal to `self`.
+    fn reveal_verify(&self, revealed: &Revealed) -> bool;
 }
 
 pub trait CommitmentLayout: CommitEncode {
@@ -311,3 +382,108 @@ impl CommitmentId for StrictHash {
 impl From<Sha256> for StrictHash {
     fn from(hash: Sha256) -> Self { hash.finish().into() }
 }
+
+impl<T: CommitEncode<CommitmentId = StrictHash>> RevealVerify<T> for StrictHash {
+    fn reveal_verify(&self, revealed: &T) -> bool { revealed.commit_id() == *self }
+}
+
+/// Computes tagged hash over the strict-serialized `value`, i.e.
+/// `SHA256(SHA256(tag) || SHA256(tag) || strict_serialize(value))`, and
+/// returns it as a commitment of type `Id` (for instance, [`StrictHash`] or
+/// [`crate::mpc::Commitment`]).
+///
+/// Unlike [`CommitId::commit_id`], this doesn't run any concealment or
+/// merklization procedures and doesn't record commitment layout.
+pub fn tagged_commit<Id: From<Sha256>, T: StrictEncode>(tag: &str, value: &T) -> Id {
+    let mut hasher = Sha256::from_tag(tag);
+    let writer = StreamWriter::new::<COMMIT_MAX_LEN>(&mut hasher);
+    let ok = value.strict_write(writer).is_ok();
+    debug_assert!(ok);
+    hasher.into()
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::hex::FromHex;
+
+    use super::*;
+    use crate::mpc::{Commitment, Leaf, Message, ProtocolId};
+    use crate::ReservedBytes;
+
+    #[test]
+    fn commitment_tag() {
+        assert_eq!(
+            Commitment::commitment_tag().to_vec(),
+            Vec::from_hex("dff876d947b03f75f7e543c479cfd41705fcc5d448b412218e312b7988fce8ac")
+                .unwrap()
+        );
+        assert_eq!(
+            StrictHash::commitment_tag().to_vec(),
+            Vec::from_hex("1b781b3ee85952bcaf2abaded2e022cefb3a4f01182ede012fcc9003313c567f")
+                .unwrap()
+        );
+        assert_eq!(Commitment::commitment_urn(), Some(Commitment::TAG));
+
+        let mut engine = Sha256::default();
+        engine.update(Commitment::commitment_tag());
+        engine.update(Commitment::commitment_tag());
+        engine.update(b"message");
+        let mut tagged = Sha256::from_tag(Commitment::TAG);
+        tagged.update(b"message");
+        assert_eq!(engine.finish(), tagged.finish());
+    }
+
+    #[test]
+    fn reveal_verify() {
+        let commitment = ReservedBytes::<4>::default().commit_id();
+        assert!(commitment.reveal_verify(&ReservedBytes::<4>::default()));
+        assert!(!commitment.reveal_verify(&ReservedBytes::<4, 1>::default()));
+        assert!(!commitment.reveal_verify(&ReservedBytes::<5>::default()));
+
+        let leaf = Leaf::entropy(0xcafe, 1);
+        let commitment = leaf.commit_id();
+        assert!(commitment.reveal_verify(&leaf));
+        assert!(!commitment.reveal_verify(&Leaf::entropy(0xcafe, 2)));
+    }
+
+    #[test]
+    fn commit_to_hash_map() {
+        let entries = (0u8..16)
+            .map(|no| (ProtocolId::from([no; 32]), Message::from([!no; 32])))
+            .collect::<Vec<_>>();
+
+        let commit = |map: &Confined<HashMap<ProtocolId, Message>, 0, 32>| {
+            let mut engine = CommitEngine::new(StrictHash::TAG);
+            engine.commit_to_hash_map(map);
+            engine.set_finished();
+            engine.finish_layout()
+        };
+        let map1 = Confined::try_from_iter(entries.iter().copied()).unwrap();
+        let map2 = Confined::try_from_iter(entries.iter().rev().copied()).unwrap();
+        let (hash1, layout1) = commit(&map1);
+        let (hash2, layout2) = commit(&map2);
+        assert_eq!(hash1.finish(), hash2.finish());
+        assert_eq!(layout1, layout2);
+
+        let btree = Confined::<BTreeMap<_, _>, 0, 32>::try_from_iter(entries).unwrap();
+        let mut engine = CommitEngine::new(StrictHash::TAG);
+        engine.commit_to_map(&btree);
+        engine.set_finished();
+        let (hash3, layout3) = engine.finish_layout();
+        assert_eq!(commit(&map1).0.finish(), hash3.finish());
+        assert_eq!(layout1, layout3);
+    }
+
+    #[test]
+    fn tagged_commit() {
+        let value = 0xcafe_babe_u64;
+        let commitment: Commitment = super::tagged_commit(Commitment::TAG, &value);
+
+        let mut engine = Sha256::from_tag(Commitment::TAG);
+        engine.update(value.to_le_bytes());
+        assert_eq!(commitment, Commitment::from(engine));
+
+        let other: Commitment = super::tagged_commit(StrictHash::TAG, &value);
+        assert_ne!(commitment, other);
+    }
+}
@@ -53,7 +53,9 @@ mod id;
 #[cfg(feature = "stl")]
 pub mod stl;
 
+pub mod dag;
 pub mod merkle;
+pub mod mmr;
 pub mod mpc;
 mod digest;
 pub mod vesper;
@@ -61,13 +63,18 @@ pub mod vesper;
 pub use commit::{CommitVerify, TryCommitVerify, VerifyError};
 pub use conceal::Conceal;
 pub use convolve::{ConvolveCommit, ConvolveCommitProof, ConvolveVerifyError};
+pub use dag::{DagNode, DagNodeId};
 pub use digest::{Digest, DigestExt, Ripemd160, Sha256};
 pub use embed::{EmbedCommitProof, EmbedCommitVerify, EmbedVerifyError, VerifyEq};
 pub use id::{
-    CommitColType, CommitEncode, CommitEngine, CommitId, CommitLayout, CommitStep, CommitmentId,
-    CommitmentLayout, StrictHash,
+    tagged_commit, CommitColType, CommitEncode, CommitEngine, CommitId, CommitLayout, CommitStep,
+    CommitmentId, CommitmentLayout, RevealVerify, StrictHash,
 };
-pub use merkle::{MerkleBuoy, MerkleHash, MerkleLeaves, MerkleNode, NodeBranching};
+pub use merkle::{
+    FieldDisclosureProof, MerkleBuoy, MerkleHash, MerkleLeaves, MerkleNode, NodeBranching,
+    RangeProof,
+};
+pub use mmr::{ExtensionProof, InclusionProof, LogCommitment, LogId, LogSubtree, MerkleLog};
 
 pub const LIB_NAME_COMMIT_VERIFY: &str = "CommitVerify";
 
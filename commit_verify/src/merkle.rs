@@ -21,7 +21,7 @@
 
 use core::{iter, slice};
 use std::collections::{btree_set, BTreeSet};
-use std::ops::SubAssign;
+use std::ops::{Range, RangeInclusive, SubAssign};
 
 use amplify::confinement::Confined;
 use amplify::num::{u256, u5};
@@ -30,7 +30,9 @@ use sha2::Sha256;
 use strict_encoding::StrictEncode;
 
 use crate::digest::DigestExt;
-use crate::{CommitId, CommitmentId, LIB_NAME_COMMIT_VERIFY};
+use crate::{
+    CommitEncode, CommitEngine, CommitId, CommitmentId, RevealVerify, LIB_NAME_COMMIT_VERIFY,
+};
 
 /// Type of merkle node branching.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -126,6 +128,10 @@ impl From<Sha256> for MerkleHash {
     fn from(hash: Sha256) -> Self { hash.finish().into() }
 }
 
+impl<T: CommitEncode<CommitmentId = MerkleHash>> RevealVerify<T> for MerkleHash {
+    fn reveal_verify(&self, revealed: &T) -> bool { revealed.commit_id() == *self }
+}
+
 const VIRTUAL_LEAF: MerkleHash = MerkleHash(Bytes32::from_array([0xFF; 32]));
 
 impl MerkleHash {
@@ -153,34 +159,61 @@ impl MerkleHash {
     ///
     /// [LNPBP-81]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0081.md
     pub fn merklize(leaves: &impl MerkleLeaves) -> Self {
-        let mut nodes = leaves.merkle_leaves().map(|leaf| leaf.commit_id());
+        Self::merklize_with(&CommitEngine::new(MerkleHash::TAG), leaves)
+    }
+
+    /// Runs [`MerkleHash::merklize`] procedure for a batch of independent
+    /// leaf sets, returning merkle root for each of them in the same order.
+    ///
+    /// The tagged hash engine is set up just once and re-used for all leafs
+    /// and nodes across all of the sets.
+    pub fn merklize_batch<L: MerkleLeaves>(sets: &[&L]) -> Vec<Self> {
+        let engine = CommitEngine::new(MerkleHash::TAG);
+        sets.iter()
+            .map(|leaves| Self::merklize_with(&engine, *leaves))
+            .collect()
+    }
+
+    fn merklize_with(engine: &CommitEngine, leaves: &impl MerkleLeaves) -> Self {
+        let mut nodes = leaves
+            .merkle_leaves()
+            .map(|leaf| Self::commit_with(engine, &leaf));
         let base_width =
             u32::try_from(nodes.len()).expect("too many merkle leaves (more than 2^31)");
         if base_width == 1 {
             // If we have just one leaf, it's MerkleNode value is the root
             nodes.next().expect("length is 1")
         } else {
-            Self::_merklize(nodes, u5::ZERO, base_width, base_width)
+            Self::_merklize(engine, nodes, u5::ZERO, base_width, base_width)
         }
     }
 
+    fn commit_with(engine: &CommitEngine, value: &impl CommitEncode<CommitmentId = Self>) -> Self {
+        let mut engine = engine.clone();
+        value.commit_encode(&mut engine);
+        engine.set_finished();
+        engine.finish().into()
+    }
+
     fn _merklize(
+        engine: &CommitEngine,
         mut iter: impl ExactSizeIterator<Item = MerkleHash>,
         depth: u5,
         branch_width: u32,
         base_width: u32,
     ) -> Self {
         if branch_width <= 2 {
-            match (iter.next(), iter.next()) {
-                (None, None) => MerkleHash::void(depth, base_width),
+            let node = match (iter.next(), iter.next()) {
+                (None, None) => MerkleNode::void(depth, base_width),
                 // Here, a single node means Merkle tree width non-equal to the power of 2, thus we
                 // need to process it with a special encoding.
-                (Some(branch), None) => MerkleHash::single(depth, base_width, branch),
+                (Some(branch), None) => MerkleNode::single(depth, base_width, branch),
                 (Some(branch1), Some(branch2)) => {
-                    MerkleHash::branches(depth, base_width, branch1, branch2)
+                    MerkleNode::branches(depth, base_width, branch1, branch2)
                 }
                 (None, Some(_)) => unreachable!(),
-            }
+            };
+            Self::commit_with(engine, &node)
         } else {
             let div = branch_width / 2 + branch_width % 2;
 
@@ -192,11 +225,208 @@ impl MerkleHash {
                 // TODO: Do this without allocation
                 .collect::<Vec<_>>()
                 .into_iter();
-            let branch1 = Self::_merklize(slice, depth + 1, div, base_width);
-            let branch2 = Self::_merklize(iter, depth + 1, branch_width - div, base_width);
+            let branch1 = Self::_merklize(engine, slice, depth + 1, div, base_width);
+            let branch2 = Self::_merklize(engine, iter, depth + 1, branch_width - div, base_width);
+
+            Self::commit_with(engine, &MerkleNode::branches(depth, base_width, branch1, branch2))
+        }
+    }
+}
 
-            MerkleHash::branches(depth, base_width, branch1, branch2)
+/// Proof that a single leaf (like an independently merklized field of a
+/// structure) is a part of the merkle tree, which does not reveal other leaves.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FieldDisclosureProof {
+    index: u32,
+    width: u32,
+    path: Vec<MerkleHash>,
+}
+
+impl FieldDisclosureProof {
+    /// Index of the disclosed leaf.
+    pub fn index(&self) -> u32 { self.index }
+
+    /// Total number of leaves in the merkle tree.
+    pub fn width(&self) -> u32 { self.width }
+
+    /// Computes merkle root from the disclosed leaf value and the proof path.
+    ///
+    /// Returns `None` if the proof path does not match the tree shape defined
+    /// by the leaf index and tree width.
+    pub fn convolve(
+        &self,
+        leaf: &impl CommitEncode<CommitmentId = MerkleHash>,
+    ) -> Option<MerkleHash> {
+        if self.index >= self.width {
+            return None;
+        }
+        let width = self.width;
+        let mut node = leaf.commit_id();
+        if width == 1 {
+            return self.path.is_empty().then_some(node);
+        }
+
+        let mut steps = vec![];
+        let (mut start, mut branch_width, mut depth) = (0u32, width, 0u8);
+        while branch_width > 2 {
+            let div = branch_width / 2 + branch_width % 2;
+            let left = self.index < start + div;
+            steps.push((depth, left));
+            if left {
+                branch_width = div;
+            } else {
+                start += div;
+                branch_width -= div;
+            }
+            depth += 1;
+        }
+
+        let mut siblings = self.path.iter().rev().copied();
+        node = match (branch_width, self.index - start) {
+            (1, _) => MerkleHash::single(depth, width, node),
+            (_, 0) => MerkleHash::branches(depth, width, node, siblings.next()?),
+            _ => MerkleHash::branches(depth, width, siblings.next()?, node),
+        };
+        for (depth, left) in steps.into_iter().rev() {
+            let sibling = siblings.next()?;
+            node = match left {
+                true => MerkleHash::branches(depth, width, node, sibling),
+                false => MerkleHash::branches(depth, width, sibling, node),
+            };
+        }
+        if siblings.next().is_some() {
+            return None;
+        }
+        Some(node)
+    }
+
+    /// Verifies that the leaf value is disclosed by the proof under the given
+    /// merkle root.
+    pub fn verify(
+        &self,
+        root: MerkleHash,
+        leaf: &impl CommitEncode<CommitmentId = MerkleHash>,
+    ) -> bool {
+        self.convolve(leaf) == Some(root)
+    }
+}
+
+/// Proof revealing a continuous range of leaves of a merkle tree without
+/// revealing other leaves.
+///
+/// For the trees built over sorted leaves (like ones coming from a
+/// [`BTreeSet`] or a [`std::collections::BTreeMap`] entries) the proof can be
+/// used to prove that the tree contains exactly the revealed leaves within a
+/// key range, and no other leaves from that range (see
+/// [`RangeProof::verify_key_range`]).
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RangeProof {
+    start: u32,
+    width: u32,
+    path: Vec<MerkleHash>,
+}
+
+impl RangeProof {
+    /// Index of the first revealed leaf.
+    pub fn start(&self) -> u32 { self.start }
+
+    /// Total number of leaves in the merkle tree.
+    pub fn width(&self) -> u32 { self.width }
+
+    /// Computes merkle root from the revealed leaves and the proof path.
+    ///
+    /// Returns `None` if the revealed leaves or the proof path do not match
+    /// the tree shape defined by the start index and tree width.
+    pub fn convolve<L: CommitEncode<CommitmentId = MerkleHash>>(
+        &self,
+        leaves: &[L],
+    ) -> Option<MerkleHash> {
+        let end = self.start.checked_add(u32::try_from(leaves.len()).ok()?)?;
+        if leaves.is_empty() || end > self.width {
+            return None;
+        }
+        let range = self.start..end;
+        let mut leaves = leaves.iter().map(L::commit_id);
+        let mut path = self.path.iter().copied();
+        let root = if self.width == 1 {
+            leaves.next()
+        } else {
+            self.node(&range, &mut leaves, &mut path, 0, self.width, 0)
+        };
+        if leaves.next().is_some() || path.next().is_some() {
+            return None;
+        }
+        root
+    }
+
+    fn node(
+        &self,
+        range: &Range<u32>,
+        leaves: &mut impl Iterator<Item = MerkleHash>,
+        path: &mut impl Iterator<Item = MerkleHash>,
+        offset: u32,
+        branch_width: u32,
+        depth: u8,
+    ) -> Option<MerkleHash> {
+        if offset + branch_width <= range.start || offset >= range.end {
+            return path.next();
+        }
+        let mut leaf = |index: u32| match range.contains(&index) {
+            true => leaves.next(),
+            false => path.next(),
+        };
+        Some(match branch_width {
+            1 => MerkleHash::single(depth, self.width, leaf(offset)?),
+            2 => MerkleHash::branches(depth, self.width, leaf(offset)?, leaf(offset + 1)?),
+            _ => {
+                let div = branch_width / 2 + branch_width % 2;
+                let node1 = self.node(range, leaves, path, offset, div, depth + 1)?;
+                let node2 =
+                    self.node(range, leaves, path, offset + div, branch_width - div, depth + 1)?;
+                MerkleHash::branches(depth, self.width, node1, node2)
+            }
+        })
+    }
+
+    /// Verifies that the revealed leaves are a part of the merkle tree with
+    /// the given root.
+    pub fn verify<L: CommitEncode<CommitmentId = MerkleHash>>(
+        &self,
+        root: MerkleHash,
+        leaves: &[L],
+    ) -> bool {
+        self.convolve(leaves) == Some(root)
+    }
+
+    /// Verifies that the revealed leaves are a part of the merkle tree with
+    /// the given root, and that the tree has no other leaves with keys within
+    /// the provided key range.
+    ///
+    /// The revealed leaves must have strictly increasing keys and include the
+    /// closest leaves outside the range on each side, unless the range
+    /// touches the beginning or the end of the tree. The check is sound only
+    /// for the trees which leaves are sorted by the key.
+    pub fn verify_key_range<L: CommitEncode<CommitmentId = MerkleHash>, K: Ord>(
+        &self,
+        root: MerkleHash,
+        leaves: &[L],
+        keys: RangeInclusive<K>,
+        key: impl Fn(&L) -> K,
+    ) -> bool {
+        let (first, last) = match (leaves.first(), leaves.last()) {
+            (Some(first), Some(last)) => (key(first), key(last)),
+            _ => return false,
+        };
+        if first >= *keys.start() && self.start != 0 {
+            return false;
         }
+        if last <= *keys.end() && self.start as usize + leaves.len() != self.width as usize {
+            return false;
+        }
+        if leaves.windows(2).any(|pair| key(&pair[0]) >= key(&pair[1])) {
+            return false;
+        }
+        self.verify(root, leaves)
     }
 }
 
@@ -206,6 +436,110 @@ pub trait MerkleLeaves {
     where Self: 'tmp;
 
     fn merkle_leaves(&self) -> Self::LeafIter<'_>;
+
+    /// Constructs proof disclosing a single leaf with the given index, such
+    /// that it can be verified against [`MerkleHash::merklize`] result without
+    /// revealing other leaves.
+    ///
+    /// Returns `None` if the index is out of the leaves range.
+    fn prove_field(&self, field_index: u32) -> Option<FieldDisclosureProof> {
+        let engine = CommitEngine::new(MerkleHash::TAG);
+        let nodes = self
+            .merkle_leaves()
+            .map(|leaf| MerkleHash::commit_with(&engine, &leaf))
+            .collect::<Vec<_>>();
+        let width = u32::try_from(nodes.len()).expect("too many merkle leaves (more than 2^31)");
+        if field_index >= width {
+            return None;
+        }
+
+        let mut path = vec![];
+        let (mut start, mut branch_width, mut depth) = (0usize, width, 0u8);
+        let index = field_index as usize;
+        while branch_width > 2 {
+            let div = branch_width / 2 + branch_width % 2;
+            let mid = start + div as usize;
+            let end = start + branch_width as usize;
+            if index < mid {
+                let iter = nodes[mid..end].iter().copied();
+                path.push(MerkleHash::_merklize(
+                    &engine,
+                    iter,
+                    u5::with(depth + 1),
+                    branch_width - div,
+                    width,
+                ));
+                branch_width = div;
+            } else {
+                let iter = nodes[start..mid].iter().copied();
+                path.push(MerkleHash::_merklize(&engine, iter, u5::with(depth + 1), div, width));
+                start = mid;
+                branch_width -= div;
+            }
+            depth += 1;
+        }
+        if width > 1 && branch_width == 2 {
+            path.push(nodes[start + 1 - (index - start)]);
+        }
+
+        Some(FieldDisclosureProof {
+            index: field_index,
+            width,
+            path,
+        })
+    }
+
+    /// Constructs proof revealing a continuous range of leaves, such that it
+    /// can be verified against [`MerkleHash::merklize`] result without
+    /// revealing other leaves.
+    ///
+    /// Returns `None` if the range is empty or goes beyond the leaves range.
+    fn prove_range(&self, range: Range<u32>) -> Option<RangeProof> {
+        let engine = CommitEngine::new(MerkleHash::TAG);
+        let nodes = self
+            .merkle_leaves()
+            .map(|leaf| MerkleHash::commit_with(&engine, &leaf))
+            .collect::<Vec<_>>();
+        let width = u32::try_from(nodes.len()).expect("too many merkle leaves (more than 2^31)");
+        if range.is_empty() || range.end > width {
+            return None;
+        }
+
+        let mut path = vec![];
+        if width > 1 {
+            range_path(&engine, &nodes, &range, 0, width, 0, &mut path);
+        }
+        Some(RangeProof {
+            start: range.start,
+            width,
+            path,
+        })
+    }
+
+    /// Constructs [`RangeProof`] revealing all leaves with keys within the
+    /// provided key range, together with the closest leaves outside of the
+    /// range, which are required for [`RangeProof::verify_key_range`].
+    ///
+    /// Leaves must be sorted by the key. Returns `None` if there are no
+    /// leaves.
+    fn prove_key_range<K: Ord>(
+        &self,
+        keys: RangeInclusive<K>,
+        key: impl Fn(&Self::Leaf) -> K,
+    ) -> Option<RangeProof> {
+        let width = self.merkle_leaves().len() as u32;
+        let lo = self
+            .merkle_leaves()
+            .position(|leaf| key(&leaf) >= *keys.start())
+            .map(|pos| pos as u32)
+            .unwrap_or(width);
+        let hi = self
+            .merkle_leaves()
+            .position(|leaf| key(&leaf) > *keys.end())
+            .map(|pos| pos as u32)
+            .unwrap_or(width);
+        self.prove_range(lo.saturating_sub(1)..(hi + 1).min(width))
+    }
 }
 
 impl<T, const MIN: usize> MerkleLeaves for Confined<Vec<T>, MIN, { u8::MAX as usize }>
@@ -262,6 +596,33 @@ where T: CommitId<CommitmentId = MerkleHash> + Copy
     fn merkle_leaves(&self) -> Self::LeafIter<'_> { self.iter().copied() }
 }
 
+fn range_path(
+    engine: &CommitEngine,
+    nodes: &[MerkleHash],
+    range: &Range<u32>,
+    offset: u32,
+    branch_width: u32,
+    depth: u8,
+    path: &mut Vec<MerkleHash>,
+) {
+    let width = nodes.len() as u32;
+    let (start, end) = (offset as usize, (offset + branch_width) as usize);
+    if offset + branch_width <= range.start || offset >= range.end {
+        let iter = nodes[start..end].iter().copied();
+        path.push(MerkleHash::_merklize(engine, iter, u5::with(depth), branch_width, width));
+    } else if branch_width <= 2 {
+        for index in offset..offset + branch_width {
+            if !range.contains(&index) {
+                path.push(nodes[index as usize]);
+            }
+        }
+    } else {
+        let div = branch_width / 2 + branch_width % 2;
+        range_path(engine, nodes, range, offset, div, depth + 1, path);
+        range_path(engine, nodes, range, offset + div, branch_width - div, depth + 1, path);
+    }
+}
+
 /// Helper struct to track depth when working with Merkle blocks.
 #[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct MerkleBuoy<D: Copy + Eq + SubAssign<u8> + Default> {
@@ -317,3 +678,161 @@ impl<D: Copy + Eq + SubAssign<u8> + Default> MerkleBuoy<D> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use amplify::confinement::{TinyOrdSet, TinyVec};
+
+    use super::*;
+    use crate::mpc::{Leaf, Message, ProtocolId};
+
+    #[test]
+    fn merklize_batch() {
+        let sets = (0u8..12)
+            .map(|len| {
+                TinyVec::try_from_iter((0..len).map(|no| {
+                    if no % 3 == 0 {
+                        Leaf::entropy(0xdeadbeef, no as u32)
+                    } else {
+                        Leaf::inhabited(ProtocolId::from([no; 32]), Message::from([len; 32]))
+                    }
+                }))
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let refs = sets.iter().collect::<Vec<_>>();
+        let roots = MerkleHash::merklize_batch(&refs);
+        assert_eq!(roots.len(), sets.len());
+        for (set, root) in sets.iter().zip(roots) {
+            assert_eq!(root, MerkleHash::merklize(set));
+        }
+    }
+
+    #[test]
+    fn merklize_binds_width() {
+        let a = Leaf::inhabited(ProtocolId::from([1; 32]), Message::from([1; 32]));
+        let b = Leaf::inhabited(ProtocolId::from([2; 32]), Message::from([2; 32]));
+        let placeholder = Leaf::entropy(0, 0);
+
+        let sets = [vec![a], vec![a, b], vec![a, b, placeholder], vec![a, b, b], vec![
+            a,
+            b,
+            placeholder,
+            placeholder,
+        ]];
+        let mut roots = vec![];
+        for leaves in sets {
+            let root = MerkleHash::merklize(&TinyVec::try_from(leaves).unwrap());
+            assert!(!roots.contains(&root));
+            roots.push(root);
+        }
+    }
+
+    #[test]
+    fn field_disclosure() {
+        for len in 1u8..12 {
+            let leaves =
+                TinyVec::try_from_iter((0..len).map(|no| {
+                    Leaf::inhabited(ProtocolId::from([no; 32]), Message::from([len; 32]))
+                }))
+                .unwrap();
+            let root = MerkleHash::merklize(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = leaves.prove_field(index as u32).unwrap();
+                assert_eq!(proof.index(), index as u32);
+                assert_eq!(proof.width(), len as u32);
+                assert!(proof.verify(root, leaf));
+                assert!(!proof.verify(root, &Leaf::entropy(0, 0)));
+            }
+            assert_eq!(leaves.prove_field(len as u32), None);
+        }
+    }
+
+    #[test]
+    fn field_disclosure_tampered() {
+        let leaves = TinyVec::try_from_iter(
+            (0u8..5).map(|no| Leaf::inhabited(ProtocolId::from([no; 32]), Message::from([no; 32]))),
+        )
+        .unwrap();
+        let root = MerkleHash::merklize(&leaves);
+        let mut proof = leaves.prove_field(3).unwrap();
+        proof.index = 2;
+        assert!(!proof.verify(root, &leaves[3]));
+        proof.index = 3;
+        proof.path.push(root);
+        assert_eq!(proof.convolve(&leaves[3]), None);
+    }
+
+    #[test]
+    fn range_proof() {
+        for len in 1u8..12 {
+            let leaves =
+                TinyVec::try_from_iter((0..len).map(|no| {
+                    Leaf::inhabited(ProtocolId::from([no; 32]), Message::from([len; 32]))
+                }))
+                .unwrap();
+            let root = MerkleHash::merklize(&leaves);
+            for start in 0..len as u32 {
+                for end in start + 1..=len as u32 {
+                    let proof = leaves.prove_range(start..end).unwrap();
+                    let revealed = &leaves[start as usize..end as usize];
+                    assert_eq!(proof.start(), start);
+                    assert!(proof.verify(root, revealed));
+                    assert!(!proof.verify(root, &revealed[1..]));
+                }
+                assert_eq!(leaves.prove_range(start..start), None);
+            }
+            assert_eq!(leaves.prove_range(0..len as u32 + 1), None);
+        }
+    }
+
+    #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+    #[derive(CommitEncode)]
+    #[commit_encode(crate = crate, strategy = strict, id = MerkleHash)]
+    struct Entry {
+        key: u8,
+        value: u16,
+    }
+
+    #[test]
+    fn key_range_proof() {
+        let map = (1u8..=10)
+            .map(|no| (no * 10, no as u16))
+            .collect::<BTreeMap<_, _>>();
+        let entries = TinyOrdSet::try_from_iter(map.iter().map(|(key, value)| Entry {
+            key: *key,
+            value: *value,
+        }))
+        .unwrap();
+        let sorted = entries.iter().copied().collect::<Vec<_>>();
+        let root = MerkleHash::merklize(&entries);
+
+        for (keys, revealed) in [
+            (25..=55, 1..6),
+            (0..=15, 0..2),
+            (95..=200, 8..10),
+            (33..=37, 2..4),
+            (200..=255, 9..10),
+        ] {
+            let proof = entries
+                .prove_key_range(keys.clone(), |entry| entry.key)
+                .unwrap();
+            assert_eq!(proof.start(), revealed.start as u32);
+            let leaves = &sorted[revealed];
+            assert!(proof.verify_key_range(root, leaves, keys, |entry| entry.key));
+        }
+
+        let proof = entries.prove_key_range(25..=45, |entry| entry.key).unwrap();
+        assert!(proof.verify_key_range(root, &sorted[1..5], 25..=45, |entry| entry.key));
+        assert!(!proof.verify_key_range(root, &sorted[1..5], 25..=55, |entry| entry.key));
+
+        let mut omitted = sorted[1..6].to_vec();
+        omitted.remove(2);
+        let proof = entries.prove_key_range(25..=55, |entry| entry.key).unwrap();
+        assert!(!proof.verify_key_range(root, &omitted, 25..=55, |entry| entry.key));
+    }
+}
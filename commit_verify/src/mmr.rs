@@ -0,0 +1,540 @@
+// Client-side-validation foundation libraries.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commitments to append-only logs using merkle mountain range (MMR)
+//! construction.
+//!
+//! The log entries are placed into a sequence of perfect binary merkle trees
+//! ("mountains") of decreasing height, defined by the binary representation of
+//! the number of entries in the log. The commitment to the log consists of the
+//! number of entries and the roots of all mountains ("peaks"), and allows to
+//! prove that one log commitment extends another one without disclosing the
+//! log entries.
+
+use amplify::confinement::Confined;
+use amplify::num::u256;
+use amplify::{Bytes32, Wrapper};
+use sha2::Sha256;
+use strict_encoding::{StrictDeserialize, StrictSerialize};
+
+use crate::{CommitId, CommitmentId, DigestExt, MerkleHash, LIB_NAME_COMMIT_VERIFY};
+
+/// Maximal number of mountains in the log, equal to the bit length of the log
+/// size.
+pub const MAX_PEAKS: usize = 64;
+
+/// Maximal number of subtrees in an [`ExtensionProof`]: each subtree height
+/// may be used at most twice, once while reaching the alignment of the larger
+/// subtrees and once while filling the rest of the range.
+pub const MAX_EXTENSION_SUBTREES: usize = 2 * MAX_PEAKS;
+
+/// Value of the `width` field used for all merkle nodes of the log,
+/// distinguishing them from the nodes of the merkle trees of a known width.
+const LOG_WIDTH: u256 = u256::ZERO;
+
+fn log_node(height: u8, left: MerkleHash, right: MerkleHash) -> MerkleHash {
+    MerkleHash::branches(height, LOG_WIDTH, left, right)
+}
+
+/// Locates the mountain containing entry with the given `index` in the log of
+/// `len` entries, returning the number of the mountain peak, position of the
+/// first mountain entry and the mountain height.
+fn mountain(len: u64, index: u64) -> Option<(usize, u64, u8)> {
+    let mut pos = 0u64;
+    let mut peak = 0usize;
+    for height in (0..64u8).rev() {
+        if len & (1 << height) == 0 {
+            continue;
+        }
+        let size = 1u64 << height;
+        if index < pos + size {
+            return Some((peak, pos, height));
+        }
+        pos += size;
+        peak += 1;
+    }
+    None
+}
+
+/// Root of a [`LogCommitment`], committing to the number of entries in the log
+/// and to the roots of all the log mountains.
+///
+/// Uses its own tag, distinct from the tag of the [`MerkleHash`] nodes the log
+/// is built from.
+#[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
+#[wrapper(Deref, BorrowSlice, Display, FromStr, Hex, Index, RangeOps)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", transparent)
+)]
+pub struct LogId(
+    #[from]
+    #[from([u8; 32])]
+    Bytes32,
+);
+
+impl CommitmentId for LogId {
+    const TAG: &'static str = "urn:ubideco:mmr:log#2024-02-12";
+}
+
+impl From<Sha256> for LogId {
+    fn from(hash: Sha256) -> Self { hash.finish().into() }
+}
+
+/// Commitment to an append-only log of entries.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[derive(CommitEncode)]
+#[commit_encode(crate = crate, strategy = strict, id = LogId)]
+pub struct LogCommitment {
+    /// Number of entries in the log.
+    len: u64,
+
+    /// Roots of the log mountains, starting from the highest one.
+    peaks: Confined<Vec<MerkleHash>, 0, MAX_PEAKS>,
+}
+
+impl LogCommitment {
+    /// Constructs commitment to an empty log.
+    pub fn new() -> Self { Self::default() }
+
+    /// Constructs commitment to a log with the provided entries.
+    pub fn with(entries: impl IntoIterator<Item = MerkleHash>) -> Self {
+        let mut commitment = Self::new();
+        for entry in entries {
+            commitment.append(entry);
+        }
+        commitment
+    }
+
+    /// Returns number of entries in the log.
+    pub fn len(&self) -> u64 { self.len }
+
+    /// Detects whether the log is empty.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns roots of the log mountains, starting from the highest one.
+    pub fn peaks(&self) -> &[MerkleHash] { &self.peaks }
+
+    /// Detects whether the number of peaks matches the log size, which may not
+    /// be the case for the commitments coming from untrusted sources.
+    fn is_consistent(&self) -> bool { self.peaks.len() == self.len.count_ones() as usize }
+
+    /// Computes the root of the log commitment.
+    pub fn root(&self) -> LogId { self.commit_id() }
+
+    /// Appends new entry to the log, updating the commitment.
+    ///
+    /// # Panics
+    ///
+    /// If the log already contains `u64::MAX` entries.
+    pub fn append(&mut self, entry: MerkleHash) {
+        self.push_subtree(0, entry);
+        self.len = self.len.checked_add(1).expect("log size overflow");
+    }
+
+    fn push_subtree(&mut self, mut height: u8, mut node: MerkleHash) {
+        // Each lowest bit of the log size set to one means there is a mountain
+        // of the same height we are merging with
+        let mut len = self.len >> height;
+        while len & 1 == 1 {
+            let peak = self.peaks.pop().expect("peaks match log size");
+            node = log_node(height + 1, peak, node);
+            height += 1;
+            len >>= 1;
+        }
+        self.peaks.push(node).expect("at most 64 peaks");
+    }
+
+    /// Verifies that the `later` commitment extends this commitment, i.e. that
+    /// the log committed by `self` is a prefix of the log committed by
+    /// `later`, using the provided extension `proof`.
+    pub fn verify_extension(&self, later: &LogCommitment, proof: &ExtensionProof) -> bool {
+        if later.len < self.len || !self.is_consistent() || !later.is_consistent() {
+            return false;
+        }
+        let mut acc = self.clone();
+        for LogSubtree { height, root } in proof.subtrees.iter().copied() {
+            if height >= 64 {
+                return false;
+            }
+            let size = 1u64 << height;
+            if acc.len % size != 0 ||
+                acc.len
+                    .checked_add(size)
+                    .map_or(true, |len| len > later.len)
+            {
+                return false;
+            }
+            acc.push_subtree(height, root);
+            acc.len += size;
+        }
+        acc == *later
+    }
+
+    /// Verifies that the log committed by `self` contains the `entry` at the
+    /// position specified by the inclusion `proof`.
+    pub fn verify_inclusion(&self, entry: MerkleHash, proof: &InclusionProof) -> bool {
+        if !self.is_consistent() {
+            return false;
+        }
+        let (peak, pos, height) = match mountain(self.len, proof.index) {
+            Some(mountain) => mountain,
+            None => return false,
+        };
+        if proof.path.len() != height as usize {
+            return false;
+        }
+        let offset = proof.index - pos;
+        let mut node = entry;
+        for (level, sibling) in proof.path.iter().enumerate() {
+            node = match (offset >> level) & 1 {
+                0 => log_node(level as u8 + 1, node, *sibling),
+                _ => log_node(level as u8 + 1, *sibling, node),
+            };
+        }
+        self.peaks.get(peak) == Some(&node)
+    }
+}
+
+/// Perfect merkle tree of log entries, referenced by its height and root.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+pub struct LogSubtree {
+    /// Height of the subtree.
+    pub height: u8,
+
+    /// Root of the subtree.
+    pub root: MerkleHash,
+}
+
+/// Proof that a log commitment extends some previous log commitment.
+///
+/// Consists of the roots of the perfect merkle trees covering all entries
+/// which were appended to the log after the earlier commitment, in the order
+/// of their addition.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+pub struct ExtensionProof {
+    /// Heights and root hashes of the appended subtrees.
+    subtrees: Confined<Vec<LogSubtree>, 0, MAX_EXTENSION_SUBTREES>,
+}
+
+impl StrictSerialize for ExtensionProof {}
+impl StrictDeserialize for ExtensionProof {}
+
+impl ExtensionProof {
+    /// Returns heights and root hashes of the appended subtrees.
+    pub fn subtrees(&self) -> &[LogSubtree] { &self.subtrees }
+}
+
+/// Proof that a log contains some entry at a given position.
+///
+/// Consists of the merkle path from the entry to the root of the mountain
+/// containing it, starting from the entry sibling.
+///
+/// Used as a membership witness when the log serves as an append-only
+/// accumulator: the proof is strict-encoded and sent to a verifier holding
+/// only the [`LogCommitment`] (or its [`LogId`]).
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+pub struct InclusionProof {
+    /// Position of the entry in the log.
+    index: u64,
+
+    /// Hashing partners of the nodes on the path from the entry to the
+    /// mountain peak.
+    path: Confined<Vec<MerkleHash>, 0, MAX_PEAKS>,
+}
+
+impl StrictSerialize for InclusionProof {}
+impl StrictDeserialize for InclusionProof {}
+
+impl InclusionProof {
+    /// Returns position of the entry in the log.
+    pub fn index(&self) -> u64 { self.index }
+
+    /// Returns hashing partners of the nodes on the path from the entry to the
+    /// mountain peak.
+    pub fn path(&self) -> &[MerkleHash] { &self.path }
+}
+
+/// Append-only log keeping all of its entries, which is required for
+/// constructing proofs for the log commitments.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct MerkleLog {
+    entries: Vec<MerkleHash>,
+}
+
+impl MerkleLog {
+    /// Constructs an empty log.
+    pub fn new() -> Self { Self::default() }
+
+    /// Appends new entry to the log.
+    pub fn append(&mut self, entry: MerkleHash) { self.entries.push(entry) }
+
+    /// Returns number of entries in the log.
+    pub fn len(&self) -> u64 { self.entries.len() as u64 }
+
+    /// Detects whether the log is empty.
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    /// Returns all the log entries.
+    pub fn entries(&self) -> &[MerkleHash] { &self.entries }
+
+    /// Returns commitment to the whole log.
+    pub fn commitment(&self) -> LogCommitment {
+        self.commitment_at(self.len()).expect("own length")
+    }
+
+    /// Returns commitment to the first `len` entries of the log, or `None` if
+    /// the log has less than `len` entries.
+    pub fn commitment_at(&self, len: u64) -> Option<LogCommitment> {
+        let entries = self.entries.get(..usize::try_from(len).ok()?)?;
+        Some(LogCommitment::with(entries.iter().copied()))
+    }
+
+    /// Constructs proof that the log commitment at length `to` extends log
+    /// commitment at length `from`. Returns `None` if `from` is greater than
+    /// `to`, or the log has less than `to` entries.
+    pub fn extension_proof(&self, from: u64, to: u64) -> Option<ExtensionProof> {
+        if from > to || to > self.len() {
+            return None;
+        }
+        let mut subtrees = vec![];
+        let mut pos = from;
+        while pos < to {
+            // The largest perfect subtree which is aligned to the current
+            // position and doesn't go beyond the end of the range
+            let mut height = pos.trailing_zeros().min(63) as u8;
+            while pos + (1 << height) > to {
+                height -= 1;
+            }
+            subtrees.push(LogSubtree {
+                height,
+                root: self.subtree_root(pos, height),
+            });
+            pos += 1 << height;
+        }
+        Some(ExtensionProof {
+            subtrees: Confined::try_from(subtrees).expect("at most two subtrees per height"),
+        })
+    }
+
+    /// Constructs proof that the log commitment at length `len` contains the
+    /// entry with the given `index`. Returns `None` if `index` is not less than
+    /// `len`, or the log has less than `len` entries.
+    pub fn inclusion_proof(&self, index: u64, len: u64) -> Option<InclusionProof> {
+        if len > self.len() {
+            return None;
+        }
+        let (_, pos, height) = mountain(len, index)?;
+        let offset = index - pos;
+        let path = (0..height).map(|level| {
+            let sibling = ((offset >> level) ^ 1) << level;
+            self.subtree_root(pos + sibling, level)
+        });
+        Some(InclusionProof {
+            index,
+            path: Confined::try_from_iter(path).expect("mountain height is below 64"),
+        })
+    }
+
+    fn subtree_root(&self, pos: u64, height: u8) -> MerkleHash {
+        if height == 0 {
+            return self.entries[pos as usize];
+        }
+        let half = 1u64 << (height - 1);
+        log_node(
+            height,
+            self.subtree_root(pos, height - 1),
+            self.subtree_root(pos + half, height - 1),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::U16 as U16MAX;
+
+    use super::*;
+
+    fn entry(no: u8) -> MerkleHash { MerkleHash::from([no; 32]) }
+
+    #[test]
+    fn append() {
+        let mut commitment = LogCommitment::new();
+        assert!(commitment.is_empty());
+        let mut roots = vec![commitment.root()];
+        for no in 0..20 {
+            commitment.append(entry(no));
+            assert_eq!(commitment.len(), no as u64 + 1);
+            assert_eq!(commitment.peaks().len(), commitment.len().count_ones() as usize);
+            assert!(!roots.contains(&commitment.root()));
+            roots.push(commitment.root());
+        }
+        assert_eq!(commitment, LogCommitment::with((0..20).map(entry)));
+    }
+
+    #[test]
+    fn root_tag() {
+        assert_eq!(LogId::TAG, "urn:ubideco:mmr:log#2024-02-12");
+        assert_ne!(LogId::TAG, MerkleHash::TAG);
+
+        let commitment = LogCommitment::with([entry(0), entry(1)]);
+        assert_eq!(
+            commitment.root().to_string(),
+            "7a2861a60558eb05f5ce0902c0ead8252ad4a075c52320c9803120c0bd1c921f"
+        );
+    }
+
+    #[test]
+    fn extension() {
+        let mut log = MerkleLog::new();
+        for no in 0..37 {
+            log.append(entry(no));
+        }
+        for from in 0..=37 {
+            for to in from..=37 {
+                let earlier = log.commitment_at(from).unwrap();
+                let later = log.commitment_at(to).unwrap();
+                let proof = log.extension_proof(from, to).unwrap();
+                assert!(earlier.verify_extension(&later, &proof));
+                if from != to {
+                    assert!(!later.verify_extension(&earlier, &proof));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extension_forged() {
+        let mut log = MerkleLog::new();
+        for no in 0..10 {
+            log.append(entry(no));
+        }
+        let earlier = log.commitment_at(3).unwrap();
+        let later = log.commitment();
+        let proof = log.extension_proof(3, 10).unwrap();
+
+        // Log which differs in one of the first entries
+        let mut forked = MerkleLog::new();
+        for no in 0..10 {
+            forked.append(entry(if no == 1 { 0xFF } else { no }));
+        }
+        let forked_earlier = forked.commitment_at(3).unwrap();
+        assert!(!forked_earlier.verify_extension(&later, &proof));
+        assert!(!earlier.verify_extension(&forked.commitment(), &proof));
+
+        assert!(!earlier.verify_extension(&later, &ExtensionProof::default()));
+    }
+
+    #[test]
+    fn extension_malformed() {
+        let proof = ExtensionProof {
+            subtrees: Confined::try_from(vec![LogSubtree {
+                height: 0,
+                root: entry(0),
+            }])
+            .unwrap(),
+        };
+        let later = LogCommitment::with([entry(0), entry(0)]);
+
+        let no_peaks = LogCommitment {
+            len: 1,
+            peaks: Confined::default(),
+        };
+        assert!(!no_peaks.verify_extension(&later, &proof));
+        assert!(!no_peaks.verify_inclusion(entry(0), &InclusionProof::default()));
+
+        let too_many_peaks = LogCommitment {
+            len: 0,
+            peaks: Confined::try_from(vec![entry(0); MAX_PEAKS]).unwrap(),
+        };
+        let first = LogCommitment::with([entry(0)]);
+        assert!(!too_many_peaks.verify_extension(&first, &proof));
+        assert!(!LogCommitment::new().verify_extension(&too_many_peaks, &proof));
+    }
+
+    #[test]
+    fn extension_encoding() {
+        let mut log = MerkleLog::new();
+        for no in 0..37 {
+            log.append(entry(no));
+        }
+        let earlier = log.commitment_at(5).unwrap();
+        let later = log.commitment();
+        let proof = log.extension_proof(5, 37).unwrap();
+
+        let data = proof.to_strict_serialized::<U16MAX>().unwrap();
+        let decoded = ExtensionProof::from_strict_serialized::<U16MAX>(data).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(earlier.verify_extension(&later, &decoded));
+    }
+
+    #[test]
+    fn inclusion() {
+        let mut log = MerkleLog::new();
+        for no in 0..37 {
+            log.append(entry(no));
+        }
+        for len in 0..=37 {
+            let commitment = log.commitment_at(len).unwrap();
+            for index in 0..len {
+                let proof = log.inclusion_proof(index, len).unwrap();
+                assert!(commitment.verify_inclusion(entry(index as u8), &proof));
+                assert!(!commitment.verify_inclusion(entry(0xFF), &proof));
+                if index > 0 {
+                    assert!(!commitment.verify_inclusion(entry(index as u8 - 1), &proof));
+                }
+            }
+            assert_eq!(log.inclusion_proof(len, len), None);
+        }
+        assert_eq!(log.inclusion_proof(0, 38), None);
+    }
+
+    #[test]
+    fn inclusion_encoding() {
+        let mut log = MerkleLog::new();
+        for no in 0..37 {
+            log.append(entry(no));
+        }
+        let commitment = log.commitment();
+        let proof = log.inclusion_proof(21, 37).unwrap();
+
+        let data = proof.to_strict_serialized::<U16MAX>().unwrap();
+        let decoded = InclusionProof::from_strict_serialized::<U16MAX>(data).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(commitment.verify_inclusion(entry(21), &decoded));
+        assert!(!commitment.verify_inclusion(entry(20), &decoded));
+        assert!(!log
+            .commitment_at(21)
+            .unwrap()
+            .verify_inclusion(entry(21), &decoded));
+    }
+}
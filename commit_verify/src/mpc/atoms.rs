@@ -19,14 +19,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use amplify::confinement::MediumOrdMap;
+use std::collections::BTreeMap;
+
+use amplify::confinement::{self, Confined, MediumOrdMap};
 use amplify::num::u5;
 use amplify::{Bytes32, FromSliceError, Wrapper};
 use sha2::Sha256;
 use strict_encoding::StrictDumb;
 
 use crate::merkle::MerkleHash;
-use crate::{CommitmentId, DigestExt};
+use crate::mpc::MerkleTree;
+use crate::{CommitEncode, CommitId, CommitmentId, DigestExt, RevealVerify};
 
 pub const MPC_MINIMAL_DEPTH: u5 = u5::with(3);
 
@@ -140,12 +143,38 @@ impl Commitment {
     pub fn copy_from_slice(slice: &[u8]) -> Result<Self, FromSliceError> {
         Bytes32::copy_from_slice(slice).map(Self)
     }
+
+    /// Compares two commitments in constant time, such that the timing of the
+    /// comparison does not leak the position of the first differing byte.
+    pub fn audit_eq(&self, other: &Self) -> bool {
+        let diff = self
+            .0
+            .iter()
+            .zip(other.0.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        diff == 0
+    }
+
+    /// Returns stable string representation of the commitment for audit logs
+    /// and comparison tooling, consisting of the commitment tag and the
+    /// lowercase hex of the commitment value, separated by `:`.
+    pub fn to_audit_string(&self) -> String { format!("{}:{}", Self::TAG, self) }
 }
 
 impl From<Sha256> for Commitment {
     fn from(hasher: Sha256) -> Self { hasher.finish().into() }
 }
 
+impl<T: CommitEncode<CommitmentId = Commitment>> RevealVerify<T> for Commitment {
+    fn reveal_verify(&self, revealed: &T) -> bool { revealed.commit_id() == *self }
+}
+
+/// Allows using commitment of a nested LNPBP-4 tree as a message in a parent
+/// tree.
+impl From<Commitment> for Message {
+    fn from(commitment: Commitment) -> Self { Message(commitment.into_inner()) }
+}
+
 /// Structured source multi-message data for commitment creation
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct MultiSource {
@@ -175,4 +204,52 @@ impl MultiSource {
             ..default!()
         }
     }
+
+    /// Constructs source for a parent LNPBP-4 tree committing to a set of
+    /// nested trees, each of which is placed under a given protocol id using
+    /// its [`Commitment`] as the message.
+    ///
+    /// An inclusion of a message into the parent tree is proved by composing
+    /// the merkle proof of the message in the nested tree with the merkle proof
+    /// of the nested tree commitment in the parent tree.
+    ///
+    /// # Errors
+    ///
+    /// If the number of nested trees exceeds the maximal number of messages in
+    /// [`MessageMap`].
+    pub fn from_subtrees(
+        subtrees: BTreeMap<ProtocolId, MerkleTree>,
+    ) -> Result<Self, confinement::Error> {
+        let messages = subtrees
+            .into_iter()
+            .map(|(protocol_id, tree)| (protocol_id, Message::from(tree.commit_id())));
+        Ok(MultiSource {
+            messages: Confined::try_from_iter(messages)?,
+            ..default!()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn commitment_audit() {
+        let commitment1 = Commitment::from([0xa5; 32]);
+        let commitment2 = Commitment::from([0xa5; 32]);
+        let mut bytes = [0xa5; 32];
+        bytes[31] = 0xa4;
+        let commitment3 = Commitment::from(bytes);
+
+        assert!(commitment1.audit_eq(&commitment2));
+        assert!(!commitment1.audit_eq(&commitment3));
+
+        assert_eq!(commitment1.to_audit_string(), commitment2.to_audit_string());
+        assert_ne!(commitment1.to_audit_string(), commitment3.to_audit_string());
+        assert_eq!(
+            commitment1.to_audit_string(),
+            format!("urn:ubideco:mpc:commitment#2024-01-31:{}", "a5".repeat(32))
+        );
+    }
 }
@@ -73,6 +73,27 @@ pub enum MergeError {
     },
 }
 
+/// errors verifying LNPBP-4 merkle proof against a commitment with a known
+/// tree depth.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ProofDepthError {
+    /// merkle proof has depth {actual}, while the depth of the committed tree
+    /// is {expected}.
+    DepthMismatch { expected: u5, actual: u8 },
+
+    #[from]
+    #[display(inner)]
+    InvalidProof(InvalidProof),
+
+    /// merkle proof doesn't produce the expected commitment {expected} for the
+    /// provided message (the proof commits to {actual}).
+    CommitmentMismatch {
+        expected: Commitment,
+        actual: Commitment,
+    },
+}
+
 /// LNPBP-4 Merkle tree node.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
@@ -212,11 +233,28 @@ impl MerkleBlock {
         protocol_id: ProtocolId,
         message: Message,
     ) -> Result<Self, InvalidProof> {
-        let path = proof.as_path();
-        let mut pos = proof.pos;
-        let mut width = proof.width();
+        Self::with_path(
+            proof.as_path(),
+            proof.pos,
+            proof.cofactor,
+            protocol_id,
+            TreeNode::CommitmentLeaf {
+                protocol_id,
+                message,
+            },
+        )
+    }
 
-        let expected = protocol_id_pos(protocol_id, proof.cofactor, width);
+    fn with_path(
+        path: &[MerkleHash],
+        mut pos: u32,
+        cofactor: u16,
+        protocol_id: ProtocolId,
+        leaf: TreeNode,
+    ) -> Result<Self, InvalidProof> {
+        let mut width = 2u32.pow(path.len() as u32);
+
+        let expected = protocol_id_pos(protocol_id, cofactor, width);
         if expected != pos {
             return Err(InvalidProof {
                 protocol_id,
@@ -244,17 +282,14 @@ impl MerkleBlock {
 
         let mut cross_section = Vec::with_capacity(path.len() + 1);
         cross_section.extend(dir);
-        cross_section.push(TreeNode::CommitmentLeaf {
-            protocol_id,
-            message,
-        });
+        cross_section.push(leaf);
         cross_section.extend(rev.into_iter().rev());
         let cross_section =
             NonEmptyVec::try_from(cross_section).expect("tree width guarantees are broken");
 
         Ok(MerkleBlock {
             depth: u5::with(path.len() as u8),
-            cofactor: proof.cofactor,
+            cofactor,
             cross_section,
             entropy: None,
         })
@@ -652,6 +687,10 @@ impl MerkleProof {
 
     /// Convolves the proof with the `message` under the given `protocol_id`,
     /// producing [`Commitment`].
+    ///
+    /// A commitment containing the message is verified by comparing it with
+    /// the returned value, which requires just the proof for the protocol and
+    /// not the whole [`MerkleTree`]. See also [`Self::verify_with_depth`].
     pub fn convolve(
         &self,
         protocol_id: ProtocolId,
@@ -660,6 +699,112 @@ impl MerkleProof {
         let block = MerkleBlock::with(self, protocol_id, message)?;
         Ok(block.commit_id())
     }
+
+    /// Verifies that the proof convolved with the `message` under the given
+    /// `protocol_id` produces the `commitment`, and that the proof has the
+    /// same depth as the committed tree.
+    ///
+    /// Checking the depth prevents accepting a proof with a path shorter (or
+    /// longer) than the one defined by the LNPBP-4 tree the commitment was
+    /// created for.
+    pub fn verify_with_depth(
+        &self,
+        commitment: Commitment,
+        protocol_id: ProtocolId,
+        message: Message,
+        expected_depth: u5,
+    ) -> Result<(), ProofDepthError> {
+        if self.depth() != expected_depth.to_u8() {
+            return Err(ProofDepthError::DepthMismatch {
+                expected: expected_depth,
+                actual: self.depth(),
+            });
+        }
+        let actual = self.convolve(protocol_id, message)?;
+        if actual != commitment {
+            return Err(ProofDepthError::CommitmentMismatch {
+                expected: commitment,
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A proof that a protocol id is not a part of the LNPBP-4 commitment, since
+/// the tree slot to which the protocol id is mapped holds an entropy
+/// placeholder.
+///
+/// The proof reveals the position of the slot, the tree cofactor and the
+/// concealed hashes of the slot leaf and of its hashing partners, but not the
+/// tree entropy; thus, like with a concealed [`MerkleBlock`], it doesn't allow
+/// to learn which other tree slots are occupied. Without the entropy the
+/// verifier can't distinguish the placeholder from a concealed leaf; parties
+/// knowing the entropy may check it with [`NonInclusionProof::is_placeholder`].
+#[derive(Getters, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
+pub struct NonInclusionProof {
+    /// Position of the placeholder leaf in the tree.
+    #[getter(as_copy)]
+    pub(super) pos: u32,
+
+    /// Cofactor used by the Merkle tree.
+    #[getter(as_copy)]
+    pub(super) cofactor: u16,
+
+    /// Concealed placeholder leaf of the slot.
+    #[getter(as_copy)]
+    pub(super) leaf: MerkleHash,
+
+    /// Merkle proof path consisting of node hashing partners.
+    #[getter(skip)]
+    pub(super) path: Confined<Vec<MerkleHash>, 0, 32>,
+}
+
+impl Default for NonInclusionProof {
+    fn default() -> Self {
+        NonInclusionProof {
+            pos: 0,
+            cofactor: 0,
+            leaf: MerkleHash::from([0u8; 32]),
+            path: empty!(),
+        }
+    }
+}
+
+impl NonInclusionProof {
+    /// Computes the depth of the merkle tree.
+    pub fn depth(&self) -> u8 { self.path.len() as u8 }
+
+    /// Checks that the slot leaf is the placeholder produced with the given
+    /// tree `entropy`.
+    pub fn is_placeholder(&self, entropy: u64) -> bool {
+        self.leaf == Leaf::entropy(entropy, self.pos).commit_id()
+    }
+
+    /// Returns inner merkle path representation
+    pub fn as_path(&self) -> &[MerkleHash] { &self.path }
+
+    /// Convolves the proof with the placeholder leaf at the position of the
+    /// given `protocol_id`, producing [`Commitment`].
+    pub fn convolve(&self, protocol_id: ProtocolId) -> Result<Commitment, InvalidProof> {
+        let leaf = TreeNode::ConcealedNode {
+            depth: u5::with(self.depth()),
+            hash: self.leaf,
+        };
+        let block = MerkleBlock::with_path(&self.path, self.pos, self.cofactor, protocol_id, leaf)?;
+        Ok(block.commit_id())
+    }
+
+    /// Verifies that the `commitment` doesn't contain any message under the
+    /// given `protocol_id`.
+    pub fn verify(&self, commitment: &Commitment, protocol_id: ProtocolId) -> bool {
+        self.convolve(protocol_id)
+            .map(|actual| actual == *commitment)
+            .unwrap_or(false)
+    }
 }
 
 #[cfg(test)]
@@ -756,4 +901,57 @@ mod test {
             assert_eq!(merged_block.commit_id(), mpc_tree.commit_id());
         }
     }
+
+    #[test]
+    fn verify_with_depth() {
+        let msgs = make_random_messages(9);
+        let tree = make_random_tree(&msgs);
+        let block = MerkleBlock::from(&tree);
+        let commitment = tree.commit_id();
+        let depth = tree.depth();
+
+        for (pid, msg) in &msgs {
+            let proof = block.to_merkle_proof(*pid).unwrap();
+            assert_eq!(proof.verify_with_depth(commitment, *pid, *msg, depth), Ok(()));
+            assert_eq!(
+                proof.verify_with_depth(commitment, *pid, *msg, depth - 1),
+                Err(ProofDepthError::DepthMismatch {
+                    expected: depth - 1,
+                    actual: depth.to_u8()
+                })
+            );
+            assert!(matches!(
+                proof.verify_with_depth(commitment, *pid, Message::from([0xFF; 32]), depth),
+                Err(ProofDepthError::CommitmentMismatch { .. })
+            ));
+
+            let truncated = MerkleProof {
+                pos: proof.pos % (proof.width() / 2),
+                cofactor: proof.cofactor,
+                path: Confined::try_from(proof.as_path()[1..].to_vec()).unwrap(),
+            };
+            assert_eq!(
+                truncated.verify_with_depth(commitment, *pid, *msg, depth),
+                Err(ProofDepthError::DepthMismatch {
+                    expected: depth,
+                    actual: depth.to_u8() - 1
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn light_verify() {
+        let msgs = make_random_messages(5);
+        let tree = make_random_tree(&msgs);
+        let block = MerkleBlock::from(&tree);
+        let commitment = tree.commit_id();
+
+        for (pid, msg) in &msgs {
+            let proof = block.to_merkle_proof(*pid).unwrap();
+            assert_eq!(proof.convolve(*pid, *msg), Ok(commitment));
+            assert_ne!(proof.convolve(*pid, Message::from([0xFF; 32])), Ok(commitment));
+            assert_ne!(proof.convolve(ProtocolId::from([0xFF; 32]), *msg), Ok(commitment));
+        }
+    }
 }
@@ -26,12 +26,17 @@
 mod atoms;
 mod tree;
 mod block;
+mod sparse;
 
 pub use atoms::{
     Commitment, Leaf, Message, MessageMap, MultiSource, ProtocolId, MPC_MINIMAL_DEPTH,
 };
-pub use block::{InvalidProof, LeafNotKnown, MergeError, MerkleBlock, MerkleProof};
-pub use tree::{Error, MerkleTree};
+pub use block::{
+    InvalidProof, LeafNotKnown, MergeError, MerkleBlock, MerkleProof, NonInclusionProof,
+    ProofDepthError,
+};
+pub use sparse::{SparseMerkleProof, SparseMerkleTree, SPARSE_TREE_DEPTH};
+pub use tree::{are_disjoint, minimal_depth, Error, MerkleTree, MerkleTreeBuilder};
 
 /// Marker trait for variates of LNPBP-4 commitment proofs, which differ by the
 /// amount of concealed information.
@@ -0,0 +1,336 @@
+// Client-side-validation foundation libraries.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sparse merkle tree keyed directly by 256-bit protocol ids, which is an
+//! alternative to the dense LNPBP-4 [`super::MerkleTree`].
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use amplify::confinement::Confined;
+use amplify::num::u256;
+use strict_encoding::{StrictDeserialize, StrictDumb, StrictSerialize};
+
+use crate::merkle::MerkleHash;
+use crate::mpc::{Leaf, Message, ProtocolId};
+use crate::{CommitId, LIB_NAME_COMMIT_VERIFY};
+
+/// Number of levels in the sparse merkle tree, equal to the bit length of
+/// [`ProtocolId`].
+pub const SPARSE_TREE_DEPTH: u16 = 256;
+
+/// Number of node hashes in each [`SparseMerkleProof`].
+const SPARSE_PATH_LEN: usize = SPARSE_TREE_DEPTH as usize;
+
+/// Value of the `width` field used for all nodes of the sparse merkle tree,
+/// distinguishing them from the nodes of the dense LNPBP-4 trees.
+const SPARSE_TREE_WIDTH: u256 = u256::MAX;
+
+fn protocol_id_bit(protocol_id: ProtocolId, depth: u16) -> bool {
+    let bytes = protocol_id.to_byte_array();
+    bytes[depth as usize / 8] & (0x80 >> (depth % 8)) != 0
+}
+
+fn branches(depth: u16, node1: MerkleHash, node2: MerkleHash) -> MerkleHash {
+    MerkleHash::branches(depth as u8, SPARSE_TREE_WIDTH, node1, node2)
+}
+
+/// Hash of the leaf which is not occupied by any protocol.
+fn empty_leaf() -> MerkleHash { MerkleHash::void(0u8, SPARSE_TREE_WIDTH) }
+
+/// Returns hashes of empty subtrees for each of the tree depths, with the last
+/// element being the hash of an empty leaf. The hashes are computed once, on
+/// the first call.
+fn empty_subtrees() -> &'static [MerkleHash] {
+    static EMPTY_SUBTREES: OnceLock<Vec<MerkleHash>> = OnceLock::new();
+    EMPTY_SUBTREES.get_or_init(|| {
+        let mut empty = vec![empty_leaf(); SPARSE_PATH_LEN + 1];
+        for depth in (0..SPARSE_TREE_DEPTH).rev() {
+            let child = empty[depth as usize + 1];
+            empty[depth as usize] = branches(depth, child, child);
+        }
+        empty
+    })
+}
+
+/// Sparse merkle tree of depth 256, where each message is placed into the leaf
+/// at the position defined by the bits of its protocol id (most significant
+/// bit first).
+///
+/// Unlike the dense LNPBP-4 [`super::MerkleTree`], the placement of messages
+/// never collides, and thus the tree never needs to grow its depth. Each
+/// membership (or non-membership) proof consists of exactly 256 node hashes.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SparseMerkleTree {
+    messages: BTreeMap<ProtocolId, Message>,
+}
+
+impl SparseMerkleTree {
+    /// Constructs an empty tree.
+    pub fn new() -> Self { Self::default() }
+
+    /// Inserts message under the given protocol id into the tree, returning
+    /// previous message for the same protocol, if any.
+    pub fn insert(&mut self, protocol_id: ProtocolId, message: Message) -> Option<Message> {
+        self.messages.insert(protocol_id, message)
+    }
+
+    /// Returns message for the given protocol id, if present in the tree.
+    pub fn get(&self, protocol_id: ProtocolId) -> Option<Message> {
+        self.messages.get(&protocol_id).copied()
+    }
+
+    /// Returns number of messages in the tree.
+    pub fn len(&self) -> usize { self.messages.len() }
+
+    /// Detects whether the tree contains no messages.
+    pub fn is_empty(&self) -> bool { self.messages.is_empty() }
+
+    /// Computes root of the tree.
+    pub fn root(&self) -> MerkleHash { Self::subtree(empty_subtrees(), 0, &self.leaves()) }
+
+    /// Constructs proof for the leaf under the given `protocol_id`. If the
+    /// protocol is not a part of the tree, the proof may be used for proving
+    /// its absence.
+    pub fn proof(&self, protocol_id: ProtocolId) -> SparseMerkleProof {
+        let empty = empty_subtrees();
+        let leaves = self.leaves();
+        let mut leaves = leaves.as_slice();
+        let mut path = Vec::with_capacity(SPARSE_PATH_LEN);
+        for depth in 0..SPARSE_TREE_DEPTH {
+            let split = leaves.partition_point(|(id, _)| !protocol_id_bit(*id, depth));
+            let (left, right) = leaves.split_at(split);
+            let (own, other) = match protocol_id_bit(protocol_id, depth) {
+                false => (left, right),
+                true => (right, left),
+            };
+            path.push(Self::subtree(empty, depth + 1, other));
+            leaves = own;
+        }
+        SparseMerkleProof {
+            path: Confined::try_from(path).expect("path has exactly one node per depth"),
+        }
+    }
+
+    fn leaves(&self) -> Vec<(ProtocolId, MerkleHash)> {
+        self.messages
+            .iter()
+            .map(|(protocol_id, message)| {
+                (*protocol_id, Leaf::inhabited(*protocol_id, *message).commit_id())
+            })
+            .collect()
+    }
+
+    fn subtree(
+        empty: &[MerkleHash],
+        depth: u16,
+        leaves: &[(ProtocolId, MerkleHash)],
+    ) -> MerkleHash {
+        match leaves {
+            [] => empty[depth as usize],
+            [(_, leaf)] if depth == SPARSE_TREE_DEPTH => *leaf,
+            _ => {
+                debug_assert!(depth < SPARSE_TREE_DEPTH, "repeated protocol ids");
+                let split = leaves.partition_point(|(id, _)| !protocol_id_bit(*id, depth));
+                let (left, right) = leaves.split_at(split);
+                branches(
+                    depth,
+                    Self::subtree(empty, depth + 1, left),
+                    Self::subtree(empty, depth + 1, right),
+                )
+            }
+        }
+    }
+}
+
+/// Membership or non-membership proof for a leaf of [`SparseMerkleTree`].
+///
+/// The proof is strict-encoded as a list of exactly [`SPARSE_TREE_DEPTH`] node
+/// hashes; data with any other number of hashes fail to decode.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+pub struct SparseMerkleProof {
+    /// Hashing partners on the path from the root to the leaf; the element
+    /// at index `i` is the sibling of the path node at depth `i + 1`.
+    path: Confined<Vec<MerkleHash>, SPARSE_PATH_LEN, SPARSE_PATH_LEN>,
+}
+
+impl StrictDumb for SparseMerkleProof {
+    fn strict_dumb() -> Self { Self::with([MerkleHash::strict_dumb(); SPARSE_PATH_LEN]) }
+}
+
+impl StrictSerialize for SparseMerkleProof {}
+impl StrictDeserialize for SparseMerkleProof {}
+
+impl SparseMerkleProof {
+    /// Constructs proof from the hashing partners on the path from the root to
+    /// the leaf, starting from the root.
+    pub fn with(path: [MerkleHash; SPARSE_PATH_LEN]) -> Self {
+        SparseMerkleProof {
+            path: Confined::try_from(path.to_vec()).expect("exact path length"),
+        }
+    }
+
+    /// Returns hashing partners of the proof, starting from the root.
+    pub fn as_path(&self) -> &[MerkleHash] { &self.path }
+
+    /// Convolves the proof with the leaf for the `protocol_id`, producing
+    /// merkle root. If `message` is `None`, the leaf is considered empty.
+    ///
+    /// Returns `None` if the proof path doesn't contain exactly
+    /// [`SPARSE_TREE_DEPTH`] node hashes.
+    pub fn convolve(
+        &self,
+        protocol_id: ProtocolId,
+        message: Option<Message>,
+    ) -> Option<MerkleHash> {
+        if self.path.len() != SPARSE_PATH_LEN {
+            return None;
+        }
+        let leaf = message
+            .map(|message| Leaf::inhabited(protocol_id, message).commit_id())
+            .unwrap_or_else(empty_leaf);
+        let root = self
+            .path
+            .iter()
+            .enumerate()
+            .rev()
+            .fold(leaf, |node, (depth, partner)| {
+                match protocol_id_bit(protocol_id, depth as u16) {
+                    false => branches(depth as u16, node, *partner),
+                    true => branches(depth as u16, *partner, node),
+                }
+            });
+        Some(root)
+    }
+
+    /// Verifies that the tree with the given `root` contains `message` under
+    /// `protocol_id`, or, if `message` is `None`, that there is no message
+    /// for the protocol in the tree.
+    pub fn verify(
+        &self,
+        protocol_id: ProtocolId,
+        message: Option<Message>,
+        root: MerkleHash,
+    ) -> bool {
+        self.convolve(protocol_id, message) == Some(root)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::U16 as U16MAX;
+
+    use super::*;
+    use crate::mpc::tree::test_helpers::make_random_messages;
+
+    #[test]
+    fn insertion() {
+        let mut tree = SparseMerkleTree::new();
+        assert!(tree.is_empty());
+        assert_eq!(tree.root(), empty_subtrees()[0]);
+
+        let protocol_id = ProtocolId::from([0x11; 32]);
+        let root = tree.root();
+        assert_eq!(tree.insert(protocol_id, Message::from([1; 32])), None);
+        assert_ne!(tree.root(), root);
+        let root = tree.root();
+        assert_eq!(tree.insert(protocol_id, Message::from([2; 32])), Some(Message::from([1; 32])));
+        assert_ne!(tree.root(), root);
+        assert_eq!(tree.get(protocol_id), Some(Message::from([2; 32])));
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn root_stability() {
+        let msgs = make_random_messages(9);
+
+        let mut tree1 = SparseMerkleTree::new();
+        for (protocol_id, message) in &msgs {
+            tree1.insert(*protocol_id, *message);
+        }
+        let mut tree2 = SparseMerkleTree::new();
+        for (protocol_id, message) in msgs.iter().rev() {
+            tree2.insert(*protocol_id, *message);
+        }
+        assert_eq!(tree1.root(), tree2.root());
+        assert_eq!(tree1.root(), tree1.root());
+    }
+
+    #[test]
+    fn proofs() {
+        let msgs = make_random_messages(5);
+        let mut tree = SparseMerkleTree::new();
+        for (protocol_id, message) in &msgs {
+            tree.insert(*protocol_id, *message);
+        }
+        let root = tree.root();
+
+        for (protocol_id, message) in &msgs {
+            let proof = tree.proof(*protocol_id);
+            assert_eq!(proof.as_path().len(), SPARSE_TREE_DEPTH as usize);
+            assert!(proof.verify(*protocol_id, Some(*message), root));
+            assert!(!proof.verify(*protocol_id, Some(Message::from([0xFF; 32])), root));
+            assert!(!proof.verify(*protocol_id, None, root));
+        }
+
+        let absent = ProtocolId::from([0xFF; 32]);
+        let proof = tree.proof(absent);
+        assert!(proof.verify(absent, None, root));
+        assert!(!proof.verify(absent, Some(Message::from([0xFF; 32])), root));
+    }
+
+    #[test]
+    fn proof_encoding() {
+        let msgs = make_random_messages(5);
+        let mut tree = SparseMerkleTree::new();
+        for (protocol_id, message) in &msgs {
+            tree.insert(*protocol_id, *message);
+        }
+        let root = tree.root();
+        let (protocol_id, message) = msgs.first_key_value().unwrap();
+
+        let proof = tree.proof(*protocol_id);
+        let data = proof.to_strict_serialized::<U16MAX>().unwrap();
+        assert_eq!(data.len(), 2 + 32 * SPARSE_PATH_LEN);
+        let decoded = SparseMerkleProof::from_strict_serialized::<U16MAX>(data.clone()).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(decoded.verify(*protocol_id, Some(*message), root));
+
+        let path: [MerkleHash; SPARSE_PATH_LEN] = proof.as_path().try_into().unwrap();
+        assert_eq!(SparseMerkleProof::with(path), proof);
+
+        // Path which is one node short
+        let mut short = data.to_vec();
+        short.truncate(short.len() - 32);
+        short[..2].copy_from_slice(&(SPARSE_PATH_LEN as u16 - 1).to_le_bytes());
+        let short = Confined::try_from(short).unwrap();
+        assert!(SparseMerkleProof::from_strict_serialized::<U16MAX>(short).is_err());
+    }
+
+    #[test]
+    fn empty_subtrees_cached() {
+        assert_eq!(empty_subtrees().len(), SPARSE_PATH_LEN + 1);
+        assert_eq!(empty_subtrees()[SPARSE_PATH_LEN], empty_leaf());
+        assert!(std::ptr::eq(empty_subtrees(), empty_subtrees()));
+    }
+}
@@ -19,14 +19,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use amplify::confinement::{LargeVec, MediumOrdMap};
+use core::iter;
+use std::collections::BTreeSet;
+
+use amplify::confinement::{Confined, LargeVec, MediumOrdMap};
 use amplify::num::{u256, u5};
 use amplify::Wrapper;
 
 pub use self::commit::Error;
 use crate::merkle::MerkleHash;
 use crate::mpc::atoms::Leaf;
-use crate::mpc::{Commitment, MerkleBlock, Message, MessageMap, Proof, ProtocolId};
+use crate::mpc::{
+    Commitment, LeafNotKnown, MerkleBlock, Message, MessageMap, NonInclusionProof, Proof,
+    ProtocolId,
+};
 use crate::{CommitId, Conceal, LIB_NAME_COMMIT_VERIFY};
 
 /// Number of cofactor variants tried before moving to the next tree depth.
@@ -74,6 +80,20 @@ impl MerkleTree {
         debug_assert_eq!(leaves.len_u32(), self.width());
         MerkleHash::merklize(&leaves)
     }
+
+    fn with_placement(depth: u5, cofactor: u16, entropy: u64, messages: MessageMap) -> Self {
+        let width = 2u32.pow(depth.to_u8() as u32);
+        let map = messages.iter().map(|(protocol, message)| {
+            (protocol_id_pos(*protocol, cofactor, width), (*protocol, *message))
+        });
+        MerkleTree {
+            depth,
+            entropy,
+            cofactor,
+            map: Confined::try_from_iter(map).expect("MultiSource type guarantees"),
+            messages,
+        }
+    }
 }
 
 impl Conceal for MerkleTree {
@@ -83,10 +103,6 @@ impl Conceal for MerkleTree {
 }
 
 mod commit {
-    use std::collections::BTreeMap;
-
-    use amplify::confinement::Confined;
-
     use super::*;
     use crate::mpc::MultiSource;
     use crate::{TryCommitVerify, UntaggedProtocol};
@@ -139,39 +155,75 @@ mod commit {
                  static entropy information in `MultiSource`",
             );
 
-            let mut map = BTreeMap::<u32, (ProtocolId, Message)>::new();
-
-            let mut depth = source.min_depth;
-            let mut prev_width = 1u32;
-            loop {
-                let width = 2u32.pow(depth.to_u8() as u32);
-                if width as usize >= msg_count {
-                    for cofactor in 0..=(prev_width.min(COFACTOR_ATTEMPTS as u32) as u16) {
-                        map.clear();
-                        if source.messages.iter().all(|(protocol, message)| {
-                            let pos = protocol_id_pos(*protocol, cofactor, width);
-                            map.insert(pos, (*protocol, *message)).is_none()
-                        }) {
-                            return Ok(MerkleTree {
-                                depth,
-                                entropy,
-                                cofactor,
-                                messages: source.messages.clone(),
-                                map: Confined::try_from(map).expect("MultiSource type guarantees"),
-                            });
-                        }
-                    }
-                }
+            let (depth, cofactor) = find_placement(
+                source.messages.keys().copied(),
+                source.min_depth,
+                source.min_depth,
+                0,
+            )?;
+            Ok(MerkleTree::with_placement(depth, cofactor, entropy, source.messages.clone()))
+        }
+    }
+}
 
-                prev_width = width;
-                depth = depth
-                    .checked_add(1)
-                    .ok_or(Error::CantFitInMaxSlots(msg_count))?;
+/// Finds the first tree depth and cofactor at which all the `protocols` get
+/// distinct leaf positions.
+///
+/// The search follows the order of LNPBP-4 commitment procedure: depths are
+/// tried starting from `depth`, and for each depth cofactors are tried in
+/// ascending order starting from `cofactor` (for the initial depth) or zero
+/// (for all subsequent depths).
+fn find_placement(
+    protocols: impl Iterator<Item = ProtocolId> + Clone,
+    min_depth: u5,
+    mut depth: u5,
+    mut cofactor: u16,
+) -> Result<(u5, u16), Error> {
+    let msg_count = protocols.clone().count();
+    let mut positions = BTreeSet::<u32>::new();
+    loop {
+        let width = 2u32.pow(depth.to_u8() as u32);
+        if width as usize >= msg_count {
+            let prev_width = if depth == min_depth { 1 } else { width / 2 };
+            let max_cofactor = prev_width.min(COFACTOR_ATTEMPTS as u32) as u16;
+            while cofactor <= max_cofactor {
+                positions.clear();
+                if protocols
+                    .clone()
+                    .all(|protocol| positions.insert(protocol_id_pos(protocol, cofactor, width)))
+                {
+                    return Ok((depth, cofactor));
+                }
+                cofactor += 1;
             }
         }
+
+        cofactor = 0;
+        depth = depth
+            .checked_add(1)
+            .ok_or(Error::CantFitInMaxSlots(msg_count))?;
     }
 }
 
+/// Computes depth of the LNPBP-4 tree which will be created for the given set
+/// of `protocols`, without constructing the tree itself.
+///
+/// # Errors
+///
+/// Same as for [`MerkleTree::try_commit`] when it is called for a
+/// [`MultiSource`] with the same protocol ids and minimal depth.
+///
+/// [`MultiSource`]: crate::mpc::MultiSource
+pub fn minimal_depth(protocols: &BTreeSet<ProtocolId>, min_depth: u5) -> Result<u5, Error> {
+    if min_depth == u5::ZERO && protocols.is_empty() {
+        return Err(Error::Empty);
+    }
+    if protocols.len() > 2usize.pow(u5::MAX.to_u8() as u32) {
+        return Err(Error::TooManyMessages(protocols.len()));
+    }
+    find_placement(protocols.iter().copied(), min_depth, min_depth, 0).map(|(depth, _)| depth)
+}
+
 pub(super) fn protocol_id_pos(protocol_id: ProtocolId, cofactor: u16, width: u32) -> u32 {
     debug_assert_ne!(width, 0);
     let rem = u256::from_le_bytes((*protocol_id).into_inner()) %
@@ -191,6 +243,190 @@ impl MerkleTree {
     pub fn depth(&self) -> u5 { self.depth }
 
     pub fn entropy(&self) -> u64 { self.entropy }
+
+    /// Returns set of all protocol ids committed to by the tree.
+    pub fn protocols(&self) -> BTreeSet<ProtocolId> { self.messages.keys().copied().collect() }
+
+    /// Constructs proof that the tree doesn't commit to any message under the
+    /// given `protocol_id`.
+    ///
+    /// Returns `None` if the tree slot to which the protocol id is mapped is
+    /// not an entropy placeholder, i.e. it is occupied either by the protocol
+    /// itself or by some other protocol.
+    pub fn non_inclusion_proof(&self, protocol_id: ProtocolId) -> Option<NonInclusionProof> {
+        let pos = self.protocol_id_pos(protocol_id);
+        if self.map.contains_key(&pos) {
+            return None;
+        }
+
+        let width = self.width();
+        let mut level = (0..width)
+            .map(|pos| {
+                self.map
+                    .get(&pos)
+                    .map(|(protocol, msg)| Leaf::inhabited(*protocol, *msg))
+                    .unwrap_or_else(|| Leaf::entropy(self.entropy, pos))
+                    .commit_id()
+            })
+            .collect::<Vec<_>>();
+        let mut path = Vec::with_capacity(self.depth.to_u8() as usize);
+        let mut index = pos as usize;
+        for depth in (0..self.depth.to_u8()).rev() {
+            path.push(level[index ^ 1]);
+            level = level
+                .chunks(2)
+                .map(|pair| MerkleHash::branches(depth, width, pair[0], pair[1]))
+                .collect();
+            index /= 2;
+        }
+        path.reverse();
+
+        Some(NonInclusionProof {
+            pos,
+            cofactor: self.cofactor,
+            leaf: Leaf::entropy(self.entropy, pos).commit_id(),
+            path: Confined::try_from(path).expect("tree depth is at most 31"),
+        })
+    }
+
+    /// Constructs [`MerkleBlock`] revealing only messages for the given set of
+    /// protocols, keeping all other messages concealed. The resulting block
+    /// commits to the same [`Commitment`] as the tree.
+    ///
+    /// # Error
+    ///
+    /// If some of the protocols from `reveal` set is not a part of the tree,
+    /// errors with [`LeafNotKnown`] error.
+    pub fn prune(&self, reveal: &BTreeSet<ProtocolId>) -> Result<MerkleBlock, LeafNotKnown> {
+        let mut block = MerkleBlock::from(self);
+        block.conceal_except(reveal.iter().copied().collect::<Vec<_>>())?;
+        Ok(block)
+    }
+}
+
+/// Checks that two trees don't share any protocol ids.
+pub fn are_disjoint(a: &MerkleTree, b: &MerkleTree) -> bool {
+    a.messages
+        .keys()
+        .all(|protocol_id| !b.messages.contains_key(protocol_id))
+}
+
+/// Builder for LNPBP-4 [`MerkleTree`] accepting messages one by one, as they
+/// arrive.
+///
+/// Each call to [`MerkleTreeBuilder::push`] places the message into the tree of
+/// the current depth and cofactor, which takes `O(log n)` time. Only if the
+/// message position collides with some already placed message, the builder
+/// resumes the depth and cofactor search from the point it has stopped
+/// previously, re-placing all the messages. Since the search never restarts
+/// from the minimal depth, the total amount of work for `n` pushes is
+/// amortized to the same number of placement attempts as a single
+/// [`MerkleTree::try_commit`] run over all `n` messages.
+///
+/// The tree produced by [`MerkleTreeBuilder::finalize`] is identical to the
+/// one produced by [`MerkleTree::try_commit`] from a [`MultiSource`] with the
+/// same messages, minimal depth and static entropy.
+///
+/// [`MultiSource`]: crate::mpc::MultiSource
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MerkleTreeBuilder {
+    min_depth: u5,
+    entropy: u64,
+    depth: u5,
+    cofactor: u16,
+    messages: MessageMap,
+    positions: BTreeSet<u32>,
+}
+
+impl MerkleTreeBuilder {
+    /// Constructs an empty builder for a tree of at least `min_depth` depth,
+    /// using `entropy` for the placeholders.
+    pub fn new(min_depth: u5, entropy: u64) -> Self {
+        MerkleTreeBuilder {
+            min_depth,
+            entropy,
+            depth: min_depth,
+            cofactor: 0,
+            messages: empty!(),
+            positions: empty!(),
+        }
+    }
+
+    /// Adds a message under the given protocol id to the tree, growing the
+    /// tree depth if the message can't be placed into the current tree.
+    ///
+    /// If the protocol already has a message in the tree, the message gets
+    /// replaced.
+    ///
+    /// # Error
+    ///
+    /// Errors with [`Error::TooManyMessages`] or [`Error::CantFitInMaxSlots`]
+    /// if the message can't be added to the tree. In this case the builder is
+    /// left unmodified.
+    pub fn push(&mut self, protocol_id: ProtocolId, message: Message) -> Result<(), Error> {
+        if self.messages.contains_key(&protocol_id) {
+            self.messages
+                .insert(protocol_id, message)
+                .map_err(|_| Error::TooManyMessages(self.messages.len()))?;
+            return Ok(());
+        }
+
+        let msg_count = self.messages.len() + 1;
+        let pos = protocol_id_pos(protocol_id, self.cofactor, self.width());
+        let fits = msg_count <= self.width() as usize && !self.positions.contains(&pos);
+        let (depth, cofactor) = match fits {
+            true => (self.depth, self.cofactor),
+            false => {
+                let protocols = self.messages.keys().copied().chain(iter::once(protocol_id));
+                find_placement(protocols, self.min_depth, self.depth, self.cofactor)?
+            }
+        };
+
+        self.messages
+            .insert(protocol_id, message)
+            .map_err(|_| Error::TooManyMessages(msg_count))?;
+        self.depth = depth;
+        self.cofactor = cofactor;
+
+        if fits {
+            self.positions.insert(pos);
+        } else {
+            let width = self.width();
+            self.positions = self
+                .messages
+                .keys()
+                .map(|protocol| protocol_id_pos(*protocol, cofactor, width))
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// Computes the width of the tree at the current stage of building.
+    pub fn width(&self) -> u32 { 2u32.pow(self.depth.to_u8() as u32) }
+
+    /// Returns the depth of the tree at the current stage of building.
+    pub fn depth(&self) -> u5 { self.depth }
+
+    /// Computes the merkle root of the tree containing all the messages added
+    /// so far.
+    pub fn current_root(&self) -> MerkleHash { self.to_tree().root() }
+
+    /// Completes building, producing the [`MerkleTree`].
+    ///
+    /// # Error
+    ///
+    /// Errors with [`Error::Empty`] if no messages were added and the minimal
+    /// depth of the tree is zero.
+    pub fn finalize(self) -> Result<MerkleTree, Error> {
+        if self.min_depth == u5::ZERO && self.messages.is_empty() {
+            return Err(Error::Empty);
+        }
+        Ok(MerkleTree::with_placement(self.depth, self.cofactor, self.entropy, self.messages))
+    }
+
+    fn to_tree(&self) -> MerkleTree {
+        MerkleTree::with_placement(self.depth, self.cofactor, self.entropy, self.messages.clone())
+    }
 }
 
 #[cfg(test)]
@@ -246,14 +482,18 @@ pub(crate) mod test_helpers {
 mod test {
     use std::collections::BTreeSet;
 
+    use amplify::confinement::Confined;
     use amplify::num::u5;
     use amplify::Wrapper;
     use rand::random;
     use strict_encoding::{StreamWriter, StrictEncode};
 
     use crate::mpc::tree::test_helpers::{make_random_messages, make_random_tree};
-    use crate::mpc::MerkleBlock;
-    use crate::{CommitId, Conceal};
+    use crate::mpc::{
+        are_disjoint, minimal_depth, Error, MerkleBlock, MerkleTree, MerkleTreeBuilder, Message,
+        MultiSource, ProtocolId, MPC_MINIMAL_DEPTH,
+    };
+    use crate::{CommitId, Conceal, TryCommitVerify};
 
     #[test]
     #[should_panic(expected = "Empty")]
@@ -378,4 +618,211 @@ mod test {
         eprintln!("Cofactors: {cofacs:?}");
         assert!(davg <= 15f32);
     }
+
+    #[test]
+    fn builder_matches_batch() {
+        for size in [1, 5, 9, 17, 64] {
+            let msgs = make_random_messages(size);
+            let entropy = random::<u64>();
+
+            let mut builder = MerkleTreeBuilder::new(MPC_MINIMAL_DEPTH, entropy);
+            for (protocol_id, message) in &msgs {
+                builder.push(*protocol_id, *message).unwrap();
+            }
+
+            let source = MultiSource {
+                min_depth: MPC_MINIMAL_DEPTH,
+                messages: Confined::try_from_iter(msgs.iter().map(|(a, b)| (*a, *b))).unwrap(),
+                static_entropy: Some(entropy),
+            };
+            let tree = MerkleTree::try_commit(&source).unwrap();
+
+            assert_eq!(builder.depth(), tree.depth());
+            assert_eq!(builder.current_root(), tree.root());
+            assert_eq!(builder.finalize().unwrap(), tree);
+        }
+    }
+
+    #[test]
+    fn builder_full() {
+        let protocol_id = |no: u32| {
+            let mut id = [0u8; 32];
+            id[..4].copy_from_slice(&no.to_le_bytes());
+            ProtocolId::from(id)
+        };
+
+        // Protocol ids `0..u16::MAX` take distinct positions in a tree of
+        // depth 16, filling the message map up to its capacity.
+        let mut builder = MerkleTreeBuilder::new(u5::with(16), 0);
+        for no in 0..u16::MAX as u32 {
+            builder
+                .push(protocol_id(no), Message::from([0x01; 32]))
+                .unwrap();
+        }
+        assert_eq!(builder.depth(), u5::with(16));
+        let before = builder.clone();
+
+        // Position of this protocol collides with protocol 0, so the builder
+        // has to grow the tree before failing on the message insertion.
+        assert_eq!(
+            builder.push(protocol_id(0x10000), Message::from([0x02; 32])),
+            Err(Error::TooManyMessages(0x10000))
+        );
+        assert_eq!(builder, before);
+        assert_eq!(builder.depth(), u5::with(16));
+    }
+
+    #[test]
+    #[should_panic(expected = "Empty")]
+    fn builder_empty() { MerkleTreeBuilder::new(u5::ZERO, 0).finalize().unwrap(); }
+
+    #[test]
+    fn prune() {
+        let msgs = make_random_messages(3);
+        let tree = make_random_tree(&msgs);
+        let (protocol_id, message) = msgs.first_key_value().unwrap();
+
+        let block = tree.prune(&BTreeSet::from([*protocol_id])).unwrap();
+        assert_eq!(block.commit_id(), tree.commit_id());
+        assert_eq!(block.conceal(), tree.conceal());
+
+        let known = block.to_known_message_map();
+        assert_eq!(known.len(), 1);
+        assert_eq!(known.get(protocol_id), Some(message));
+
+        let unknown = ProtocolId::from([0xFF; 32]);
+        assert!(tree.prune(&BTreeSet::from([unknown])).is_err());
+    }
+
+    #[test]
+    fn tags_are_stable() {
+        use crate::mpc::Commitment;
+        use crate::{CommitmentId, MerkleHash};
+
+        assert_eq!(Commitment::TAG, "urn:ubideco:mpc:commitment#2024-01-31");
+        assert_eq!(MerkleHash::TAG, "urn:ubideco:merkle:node#2024-01-31");
+    }
+
+    #[test]
+    fn commitment_deterministic() {
+        let msgs = (1u8..10).map(|no| (ProtocolId::from([no; 32]), Message::from([0x10 + no; 32])));
+        let source = MultiSource {
+            min_depth: MPC_MINIMAL_DEPTH,
+            messages: Confined::try_from_iter(msgs).unwrap(),
+            static_entropy: Some(0xDEADBEEF),
+        };
+        let tree = MerkleTree::try_commit(&source).unwrap();
+        assert_eq!(tree.depth, u5::with(4));
+        assert_eq!(tree.cofactor, 0);
+        assert_eq!(
+            tree.root().to_string(),
+            "dca2dd55e40f7a22b5b12206ae9a721596f7ee939b8876048c6eca575862a7fa"
+        );
+        assert_eq!(
+            tree.commit_id().to_string(),
+            "239feea186ebca7d20b240c88da77636096784637a60f213678cc7cddf900871"
+        );
+        assert_eq!(MerkleTree::try_commit(&source).unwrap().commit_id(), tree.commit_id());
+    }
+
+    #[test]
+    fn disjoint() {
+        let msgs1 = make_random_messages(5);
+        let msgs2 = make_random_messages(7);
+        let tree1 = make_random_tree(&msgs1);
+        let tree2 = make_random_tree(&msgs2);
+
+        assert_eq!(tree1.protocols(), msgs1.keys().copied().collect());
+        assert!(are_disjoint(&tree1, &tree2));
+        assert!(are_disjoint(&tree2, &tree1));
+
+        let mut msgs3 = make_random_messages(3);
+        let (protocol_id, message) = msgs1.first_key_value().unwrap();
+        msgs3.insert(*protocol_id, *message);
+        let tree3 = make_random_tree(&msgs3);
+        assert!(!are_disjoint(&tree1, &tree3));
+        assert!(!are_disjoint(&tree3, &tree1));
+        assert!(!are_disjoint(&tree1, &tree1));
+    }
+
+    #[test]
+    fn non_inclusion_proof() {
+        let msgs = make_random_messages(9);
+        let tree = make_random_tree(&msgs);
+        let commitment = tree.commit_id();
+
+        for protocol_id in msgs.keys() {
+            assert_eq!(tree.non_inclusion_proof(*protocol_id), None);
+        }
+
+        let absent = (0u8..=0xFF)
+            .map(|no| ProtocolId::from([no; 32]))
+            .find(|id| !tree.map.contains_key(&tree.protocol_id_pos(*id)))
+            .unwrap();
+        let proof = tree.non_inclusion_proof(absent).unwrap();
+        assert_eq!(proof.depth(), tree.depth().to_u8());
+        assert!(proof.verify(&commitment, absent));
+        assert!(proof.is_placeholder(tree.entropy()));
+        assert!(!proof.is_placeholder(tree.entropy() ^ 1));
+
+        let (present, _) = msgs.first_key_value().unwrap();
+        assert!(!proof.verify(&commitment, *present));
+
+        let other = make_random_tree(&make_random_messages(9));
+        assert!(!proof.verify(&other.commit_id(), absent));
+    }
+
+    #[test]
+    fn nested() {
+        let msgs1 = make_random_messages(5);
+        let msgs2 = make_random_messages(7);
+        let subtree1 = make_random_tree(&msgs1);
+        let subtree2 = make_random_tree(&msgs2);
+        let (nested_id, nested_msg) = msgs2.first_key_value().unwrap();
+
+        let parent_id1 = ProtocolId::from([0x01; 32]);
+        let parent_id2 = ProtocolId::from([0x02; 32]);
+        let source = MultiSource::from_subtrees(
+            [(parent_id1, subtree1), (parent_id2, subtree2.clone())].into(),
+        )
+        .unwrap();
+        let parent = MerkleTree::try_commit(&source).unwrap();
+        let commitment = parent.commit_id();
+
+        let nested_proof = MerkleBlock::from(&subtree2)
+            .to_merkle_proof(*nested_id)
+            .unwrap();
+        let parent_proof = MerkleBlock::from(&parent)
+            .to_merkle_proof(parent_id2)
+            .unwrap();
+
+        let subtree_commitment = nested_proof.convolve(*nested_id, *nested_msg).unwrap();
+        assert_eq!(subtree_commitment, subtree2.commit_id());
+        let root = parent_proof
+            .convolve(parent_id2, subtree_commitment.into())
+            .unwrap();
+        assert_eq!(root, commitment);
+
+        let unnested = parent_proof.convolve(parent_id2, *nested_msg).unwrap();
+        assert_ne!(unnested, commitment);
+    }
+
+    #[test]
+    fn minimal_depth_matches_tree() {
+        for size in [1, 5, 9, 17, 64, 200] {
+            let msgs = make_random_messages(size);
+            let protocols = msgs.keys().copied().collect::<BTreeSet<_>>();
+            let tree = make_random_tree(&msgs);
+            assert_eq!(minimal_depth(&protocols, u5::ZERO).unwrap(), tree.depth());
+
+            let source = MultiSource {
+                min_depth: MPC_MINIMAL_DEPTH,
+                messages: Confined::try_from_iter(msgs.iter().map(|(a, b)| (*a, *b))).unwrap(),
+                static_entropy: Some(0),
+            };
+            let tree = MerkleTree::try_commit(&source).unwrap();
+            assert_eq!(minimal_depth(&protocols, MPC_MINIMAL_DEPTH).unwrap(), tree.depth());
+        }
+        assert!(minimal_depth(&BTreeSet::new(), u5::ZERO).is_err());
+    }
 }
@@ -37,6 +37,7 @@ pub enum Pred {
     Hashed,
     Merklized,
     Concealed,
+    Unordered,
     List,
     Set,
     Element,
@@ -97,6 +98,7 @@ impl CommitStep {
             CommitStep::Hashed(fqn) => fqn,
             CommitStep::Merklized(fqn) => fqn,
             CommitStep::Concealed(fqn) => fqn,
+            CommitStep::Unordered(fqn) => fqn,
         }
         .name
         .to_ident()
@@ -111,6 +113,7 @@ impl CommitStep {
             CommitStep::Hashed(_) => Pred::Hashed,
             CommitStep::Merklized(_) => Pred::Merklized,
             CommitStep::Concealed(_) => Pred::Concealed,
+            CommitStep::Unordered(_) => Pred::Unordered,
         }
     }
 
@@ -118,7 +121,10 @@ impl CommitStep {
         match self {
             CommitStep::Collection(_, sizing, _) => small_vec![Attr::LenRange((*sizing).into())],
             CommitStep::Concealed(from) => small_vec![Attr::Concealed(from.clone())],
-            CommitStep::Serialized(_) | CommitStep::Hashed(_) | CommitStep::Merklized(_) => none!(),
+            CommitStep::Serialized(_) |
+            CommitStep::Hashed(_) |
+            CommitStep::Merklized(_) |
+            CommitStep::Unordered(_) => none!(),
         }
     }
 
@@ -155,7 +161,8 @@ impl CommitStep {
             CommitStep::Serialized(_) |
             CommitStep::Hashed(_) |
             CommitStep::Merklized(_) |
-            CommitStep::Concealed(_) => empty!(),
+            CommitStep::Concealed(_) |
+            CommitStep::Unordered(_) => empty!(),
         }
     }
 }
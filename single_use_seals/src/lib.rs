@@ -87,10 +87,17 @@
 //! The module defines trait [`SealProtocol`] that can be used for
 //! implementation of single-use-seals with methods for seal close and
 //! verification. A type implementing this trait operates only with messages
-//! (which is represented by any type that implements `AsRef<[u8]>`,i.e. can be
-//! represented as a sequence of bytes) and witnesses (which is represented by
-//! an associated type [`SealProtocol::Witness`]). At the same time,
-//! [`SealProtocol`] can't define seals by itself.
+//! (which are represented by an associated type [`SealProtocol::Message`]) and
+//! witnesses (which are represented by an associated type
+//! [`SealProtocol::Witness`]). At the same time, [`SealProtocol`] can't define
+//! seals by itself.
+//!
+//! Messages are not required to be byte strings: a message may be any
+//! structured type, for instance a commitment produced by `commit_verify`
+//! crate (like `mpc::Commitment`). In such case the seal is closed over the
+//! commitment to the structured data, and the verifier may pass the structured
+//! data itself to `SealWitness::verify_seal_committed` (available with
+//! `commit` feature), which computes the commitment before verifying the seal.
 //!
 //! Seal protocol operates with a *seal medium *: a proof of publication medium
 //! on which the seals are defined.
@@ -118,6 +125,11 @@ extern crate amplify_derive;
 #[macro_use]
 extern crate async_trait;
 
+#[cfg(any(test, feature = "testmock"))]
+pub mod testmock;
+
+use std::fmt::Debug;
+
 /// Trait for proof-of-publication medium on which the seals are defined,
 /// closed, verified and which can be used for convenience operations related to
 /// seals:
@@ -155,6 +167,25 @@ pub trait SealProtocol<Seal> {
     /// Checks the status for a given seal in proof-of-publication medium
     fn get_seal_status(&self, seal: &Seal) -> Result<SealStatus, Self::Error>;
 
+    /// Returns the current state of the medium (like its height), which is
+    /// used as a reference point for the time-dependent checks, like
+    /// [`Self::verify_seal_fresh`]. Function has default implementation
+    /// returning [`MediumState::Unknown`].
+    ///
+    /// Mediums supporting witness validity windows (see
+    /// [`Self::witness_validity`]) MUST override this function; otherwise the
+    /// freshness of the witnesses can't be checked.
+    fn medium_state(&self) -> Result<MediumState, Self::Error> { Ok(MediumState::Unknown) }
+
+    /// Checks the status for a given seal in proof-of-publication medium,
+    /// returning it together with the state of the medium at which the status
+    /// was determined. Function has default implementation calling
+    /// [`Self::get_seal_status`] and [`Self::medium_state`].
+    fn get_seal_status_at(&self, seal: &Seal) -> Result<(SealStatus, MediumState), Self::Error> {
+        let status = self.get_seal_status(seal)?;
+        Ok((status, self.medium_state()?))
+    }
+
     /// Publishes witness data to the medium. Function has default
     /// implementation doing nothing and returning
     /// [`SealMediumError::PublicationNotSupported`] error.
@@ -185,6 +216,127 @@ pub trait SealProtocol<Seal> {
     ) -> Result<bool, SealMediumError<Self::Error>> {
         Err(SealMediumError::PublicationNotSupported)
     }
+
+    /// Returns number of seals which can be closed by a single witness, if the
+    /// medium limits it. Function has default implementation returning
+    /// `None`, i.e. no limit.
+    ///
+    /// Mediums returning a limit MUST fail [`CloseSeal::close_all_seals`] for
+    /// the number of seals exceeding it.
+    fn remaining_capacity(&self) -> Result<Option<usize>, Self::Error> { Ok(None) }
+
+    /// Returns window of the medium heights within which the witness is
+    /// considered valid. Function has default implementation doing nothing
+    /// and returning [`SealMediumError::ValidityNotSupported`] error.
+    fn witness_validity(
+        &self,
+        _witness: &Self::Witness,
+    ) -> Result<ValidityWindow, SealMediumError<Self::Error>> {
+        Err(SealMediumError::ValidityNotSupported)
+    }
+
+    /// Returns window of the medium heights within which the seal may be
+    /// closed, if the seal definition limits it. Function has default
+    /// implementation returning `None`, i.e. no limit.
+    fn seal_validity(&self, _seal: &Seal) -> Result<Option<ValidityWindow>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Checks that the seal definition is well-formed according to the medium
+    /// rules, such that the seal can be used. Function has default
+    /// implementation accepting all seals.
+    fn validate_seal_definition(&self, _seal: &Seal) -> Result<(), Self::Error> { Ok(()) }
+
+    /// Verifies that the seal was closed over the message with the provided
+    /// witness, and that the witness is valid at the current medium height
+    /// (see [`Self::witness_validity`]), rejecting stale witnesses.
+    ///
+    /// If the medium doesn't support witness validity windows, only the seal
+    /// closing is verified. If the medium supports them, but the current
+    /// medium height is not known (see [`Self::medium_state`]), errors with
+    /// [`SealMediumError::HeightUnknown`].
+    fn verify_seal_fresh(
+        &self,
+        witness: &Self::Witness,
+        seal: &Seal,
+        msg: &Self::Message,
+    ) -> Result<(), SealMediumError<Self::Error>>
+    where
+        Self::Witness: SealWitness<Seal, Message = Self::Message, Error = Self::Error>,
+    {
+        witness.verify_seal(seal, msg)?;
+        let window = match self.witness_validity(witness) {
+            Ok(window) => window,
+            Err(SealMediumError::ValidityNotSupported) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        match self.medium_state()? {
+            MediumState::Height(height) if !window.contains(height) => {
+                Err(SealMediumError::WitnessOutOfWindow { height })
+            }
+            MediumState::Height(_) => Ok(()),
+            MediumState::Unknown => Err(SealMediumError::HeightUnknown),
+        }
+    }
+
+    /// Returns medium height at which the witness was published, or `None` if
+    /// the witness is not published. Function has default implementation doing
+    /// nothing and returning [`SealMediumError::PublicationNotSupported`]
+    /// error.
+    fn get_witness_publication_height(
+        &self,
+        _witness: &Self::Witness,
+    ) -> Result<Option<u64>, SealMediumError<Self::Error>> {
+        Err(SealMediumError::PublicationNotSupported)
+    }
+
+    /// Verifies that the seal was closed over the message with the provided
+    /// witness, and that the seal is closed within its validity window (see
+    /// [`Self::seal_validity`]).
+    ///
+    /// The height at which the seal is closed is the height at which the
+    /// witness was published, as reported by
+    /// [`Self::get_witness_publication_height`], such that the result doesn't
+    /// depend on the current medium height. For seals with a validity window
+    /// errors with [`SealMediumError::WitnessNotPublished`] if the witness is
+    /// not published yet.
+    fn verify_seal_within_window(
+        &self,
+        witness: &Self::Witness,
+        seal: &Seal,
+        msg: &Self::Message,
+    ) -> Result<(), SealMediumError<Self::Error>>
+    where
+        Self::Witness: SealWitness<Seal, Message = Self::Message, Error = Self::Error>,
+    {
+        witness.verify_seal(seal, msg)?;
+        let window = match self.seal_validity(seal)? {
+            Some(window) => window,
+            None => return Ok(()),
+        };
+        match self.get_witness_publication_height(witness)? {
+            Some(height) if !window.contains(height) => {
+                Err(SealMediumError::SealExpired { height })
+            }
+            Some(_) => Ok(()),
+            None => Err(SealMediumError::WitnessNotPublished),
+        }
+    }
+
+    /// Exports the full state of the medium (like the set of closed seals) for
+    /// snapshotting or migration. Function has default implementation doing
+    /// nothing and returning [`SealMediumError::SnapshotNotSupported`] error.
+    fn export_state(&self) -> Result<Vec<u8>, SealMediumError<Self::Error>> {
+        Err(SealMediumError::SnapshotNotSupported)
+    }
+
+    /// Imports the medium state previously exported with
+    /// [`Self::export_state`], replacing the current state. Function has
+    /// default implementation doing nothing and returning
+    /// [`SealMediumError::SnapshotNotSupported`] error.
+    fn import_state(&mut self, _data: &[u8]) -> Result<(), SealMediumError<Self::Error>> {
+        Err(SealMediumError::SnapshotNotSupported)
+    }
 }
 
 /// Adds support for the seal close operation to [`SealProtocol`].
@@ -210,6 +362,13 @@ pub trait CloseSeal<Seal>: SealProtocol<Seal> {
     /// into the returned Witness type.
     ///
     /// The witness _is not_ published by this method to the seal medium.
+    ///
+    /// If the medium limits the number of seals closed by a single witness
+    /// (see [`SealProtocol::remaining_capacity`]), the implementation MUST
+    /// fail for a larger number of seals with a medium-specific error.
+    /// [`Self::close_all_seals_checked`] performs the same check before
+    /// calling this method, reporting it with the medium-independent
+    /// [`SealMediumError::CapacityExceeded`] error.
     fn close_all_seals<'seal>(
         &mut self,
         seals: impl IntoIterator<Item = &'seal Seal>,
@@ -217,6 +376,187 @@ pub trait CloseSeal<Seal>: SealProtocol<Seal> {
     ) -> Result<Self::Witness, Self::Error>
     where
         Seal: 'seal;
+
+    /// Checks whether the seal can be closed, without closing it or changing
+    /// the state of the medium. Function has default implementation
+    /// accepting all seals, leaving all checks to the close procedure.
+    ///
+    /// Mediums SHOULD override this function for
+    /// [`Self::close_all_seals_indexed`] to be able to report the exact seal
+    /// causing the failure.
+    fn check_closable(&self, _seal: &Seal) -> Result<(), Self::Error> { Ok(()) }
+
+    /// Closes number of related seals over the same message, producing a single
+    /// *witness*, like [`Self::close_all_seals`], but in case of a failure
+    /// reports the index of the seal which caused it, if known.
+    ///
+    /// The offending seal is found by running [`Self::check_closable`] for each
+    /// of the seals before closing them; if all the checks pass, the seals are
+    /// closed with a single call to [`Self::close_all_seals`]. Since the
+    /// failures of that call can't be attributed to a specific seal, they are
+    /// reported with [`SealCloseError::index`] set to `None`. Thus, the
+    /// position of the failing seal is known only for the mediums overriding
+    /// [`Self::check_closable`].
+    fn close_all_seals_indexed<'seal>(
+        &mut self,
+        seals: impl IntoIterator<Item = &'seal Seal>,
+        over: &Self::Message,
+    ) -> Result<Self::Witness, SealCloseError<Self::Error>>
+    where
+        Seal: 'seal,
+    {
+        let seals = seals.into_iter().collect::<Vec<_>>();
+        for (index, seal) in seals.iter().enumerate() {
+            self.check_closable(seal).map_err(|source| SealCloseError {
+                index: Some(index),
+                source,
+            })?;
+        }
+        self.close_all_seals(seals, over)
+            .map_err(|source| SealCloseError {
+                index: None,
+                source,
+            })
+    }
+
+    /// Closes number of related seals over the same message, producing a single
+    /// *witness*, checking that the number of seals doesn't exceed the medium
+    /// capacity reported by [`SealProtocol::remaining_capacity`].
+    ///
+    /// If there are too many seals, errors with
+    /// [`SealMediumError::CapacityExceeded`] without closing any of the seals
+    /// and without calling [`Self::close_all_seals`], such that the failure
+    /// is reported in the same way for all mediums.
+    fn close_all_seals_checked<'seal>(
+        &mut self,
+        seals: impl IntoIterator<Item = &'seal Seal>,
+        over: &Self::Message,
+    ) -> Result<Self::Witness, SealMediumError<Self::Error>>
+    where
+        Seal: 'seal,
+    {
+        let seals = seals.into_iter().collect::<Vec<_>>();
+        if let Some(capacity) = self.remaining_capacity()? {
+            if seals.len() > capacity {
+                return Err(SealMediumError::CapacityExceeded {
+                    requested: seals.len(),
+                    capacity,
+                });
+            }
+        }
+        self.close_all_seals(seals, over)
+            .map_err(SealMediumError::from)
+    }
+
+    /// Closes number of related seals over the same message, producing a single
+    /// *witness* which doesn't depend on the order in which the seals are
+    /// provided.
+    ///
+    /// Since [`CloseSeal::close_all_seals`] implementations may produce
+    /// witnesses with the layout depending on the seal order, the seals are
+    /// sorted lexicographically by their strict encoding before closing.
+    #[cfg(feature = "strict")]
+    fn close_all_seals_ordered<'seal>(
+        &mut self,
+        seals: impl IntoIterator<Item = &'seal Seal>,
+        over: &Self::Message,
+    ) -> Result<Self::Witness, Self::Error>
+    where
+        Seal: strict_encoding::StrictEncode + 'seal,
+    {
+        let mut seals = seals
+            .into_iter()
+            .map(|seal| {
+                let mut bytes = Vec::new();
+                let writer =
+                    strict_encoding::StreamWriter::new::<{ amplify::confinement::U32 }>(&mut bytes);
+                let ok = seal.strict_write(writer).is_ok();
+                debug_assert!(ok);
+                (bytes, seal)
+            })
+            .collect::<Vec<_>>();
+        seals.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.close_all_seals(seals.into_iter().map(|(_, seal)| seal), over)
+    }
+
+    /// Closes seal over a message and publishes the produced *witness* to the
+    /// seal medium, returning the witness together with its publication id.
+    ///
+    /// If the publication fails, the witness produced by closing the seal is
+    /// returned inside [`ClosePublishError::Publish`], such that the caller
+    /// may retry the publication without closing the seal over again.
+    fn close_and_publish(
+        &mut self,
+        seal: &Seal,
+        over: &Self::Message,
+    ) -> Result<(Self::Witness, Self::PublicationId), ClosePublishError<Self::Witness, Self::Error>>
+    where
+        Self::Witness: Debug,
+    {
+        let witness = self
+            .close_seal(seal, over)
+            .map_err(ClosePublishError::Close)?;
+        match self.publish_witness(&witness) {
+            Ok(publication_id) => Ok((witness, publication_id)),
+            Err(error) => Err(ClosePublishError::Publish { witness, error }),
+        }
+    }
+}
+
+/// Adds support to [`CloseSeal`] for closing seals with an externally provided
+/// nonce, such that the produced witness is fully determined by the seal, the
+/// message and the nonce.
+///
+/// Seal protocols which use randomness during seal closing SHOULD implement
+/// [`CloseSeal::close_seal`] by generating a random nonce and delegating to
+/// [`CloseSealDeterministic::close_seal_deterministic`]. The deterministic
+/// method is useful for reproducible tests and hardware signing devices.
+pub trait CloseSealDeterministic<Seal>: CloseSeal<Seal> {
+    /// Closes seal over a message, producing *witness*, using the provided
+    /// `nonce` instead of internal source of randomness.
+    ///
+    /// Calling this method twice with the same seal, message and nonce MUST
+    /// produce the same witness.
+    ///
+    /// NB: Closing of the seal MUST not change the internal state of the
+    /// seal itself; all the data produced by the process must be placed
+    /// into the returned Witness type.
+    ///
+    /// The witness _is not_ published by this method to the seal medium.
+    fn close_seal_deterministic(
+        &mut self,
+        seal: &Seal,
+        over: &Self::Message,
+        nonce: [u8; 32],
+    ) -> Result<Self::Witness, Self::Error>;
+}
+
+/// Adds support to [`SealProtocol`] for explicit revocation of seals, making
+/// them permanently unusable.
+///
+/// Mediums implementing this trait MUST fail [`CloseSeal::close_seal`] and
+/// [`CloseSeal::close_all_seals`] operations for the revoked seals.
+pub trait RevokeSeal<Seal>: SealProtocol<Seal> {
+    /// Marks the seal as revoked, such that it can't be closed anymore.
+    fn revoke_seal(&mut self, seal: &Seal) -> Result<(), Self::Error>;
+
+    /// Checks whether the seal was revoked.
+    fn is_revoked(&self, seal: &Seal) -> Result<bool, Self::Error>;
+}
+
+/// Adds support to [`SealProtocol`] for watching seals and getting notified
+/// about changes in their status (for mediums which can poll or subscribe for
+/// updates).
+///
+/// The callback is owned by the medium and is called synchronously from the
+/// same thread which processes the medium updates (for instance, the thread
+/// publishing a witness). Since the callback is not required to be [`Send`],
+/// a medium holding callbacks can't be moved between threads; callbacks MUST
+/// NOT block or call back into the medium.
+pub trait WatchSeal<Seal>: SealProtocol<Seal> {
+    /// Registers a callback which is called each time the status of the seal
+    /// changes.
+    fn watch_seal(&mut self, seal: Seal, cb: Box<dyn Fn(SealStatus)>) -> Result<(), Self::Error>;
 }
 
 /// Adds support to [`SealProtocol`] for merging seal close operation into an
@@ -266,6 +606,41 @@ pub trait SealWitness<Seal> {
     /// provided seal closure witness.
     fn verify_seal(&self, seal: &Seal, msg: &Self::Message) -> Result<(), Self::Error>;
 
+    /// Decodes the witness from its strict-serialized representation and
+    /// verifies that the seal was closed over the message with it.
+    ///
+    /// Errors happening during the witness decoding are reported as
+    /// [`VerifyBytesError::Decode`], distinct from the seal verification
+    /// errors, which are reported as [`VerifyBytesError::Verify`].
+    #[cfg(feature = "strict")]
+    fn verify_seal_from_bytes(
+        witness_bytes: &[u8],
+        seal: &Seal,
+        msg: &Self::Message,
+    ) -> Result<(), VerifyBytesError<Self::Error>>
+    where
+        Self: strict_encoding::StrictDeserialize,
+    {
+        let data = amplify::confinement::Confined::try_from(witness_bytes.to_vec())
+            .map_err(|err| strict_encoding::DecodeError::DataIntegrityError(err.to_string()))
+            .map_err(strict_encoding::DeserializeError::from)?;
+        let witness = Self::from_strict_serialized::<{ amplify::confinement::U32 }>(data)?;
+        witness
+            .verify_seal(seal, msg)
+            .map_err(VerifyBytesError::Verify)
+    }
+
+    /// Verifies that the seal was closed over the commitment to the structured
+    /// message `msg`, by computing the commitment with
+    /// [`commit_verify::CommitId::commit_id`] and passing it to
+    /// [`Self::verify_seal`].
+    #[cfg(feature = "commit")]
+    fn verify_seal_committed<T>(&self, seal: &Seal, msg: &T) -> Result<(), Self::Error>
+    where T: commit_verify::CommitEncode<CommitmentId = Self::Message> {
+        use commit_verify::CommitId;
+        self.verify_seal(seal, &msg.commit_id())
+    }
+
     /// Performs batch verification of the seals.
     ///
     /// Default implementation iterates through the seals and calls
@@ -284,6 +659,106 @@ pub trait SealWitness<Seal> {
         }
         Ok(())
     }
+
+    /// Performs batch verification of the seals, verifying all of them and
+    /// reporting all the failures.
+    ///
+    /// Unlike [`Self::verify_many_seals`], doesn't stop on the first failure;
+    /// the returned error lists the index of each failed seal together with
+    /// the reason of the failure.
+    fn verify_many_seals_report<'seal>(
+        &self,
+        seals: impl IntoIterator<Item = &'seal Seal>,
+        msg: &Self::Message,
+    ) -> Result<(), Vec<(usize, Self::Error)>>
+    where
+        Seal: 'seal,
+    {
+        let failures = seals
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, seal)| self.verify_seal(seal, msg).err().map(|err| (index, err)))
+            .collect::<Vec<_>>();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+/// Seal witness which can be aggregated from multiple witnesses produced by
+/// independent seal close operations (for instance, by different parties) into
+/// a single witness for publication.
+///
+/// Unlike [`MergeCloseSeal`], which adds seals to a witness during the seal
+/// close procedure, this trait operates on already existing witnesses.
+pub trait AggregateWitness<Seal>: SealWitness<Seal> + Sized {
+    /// Aggregates multiple witnesses into a single one, which MUST verify all
+    /// the seals verifiable by each of the original witnesses.
+    ///
+    /// Implementations MUST detect conflicts between the witnesses, such as
+    /// the same seal being closed by more than one witness, or witnesses
+    /// closing seals over different messages (if the medium requires a
+    /// single message per witness), and error in such cases.
+    fn aggregate(witnesses: Vec<Self>) -> Result<Self, Self::Error>;
+}
+
+/// Seal witness which has a compact representation, factoring out data shared
+/// between multiple closed seals (like a common anchor), which may be used to
+/// reduce the size of witnesses closing many seals at once.
+pub trait CompactWitness<Seal>: SealWitness<Seal> + Sized {
+    /// Compact representation of the witness, which MUST verify exactly the
+    /// same seals over the same messages as the original witness.
+    type Compact: SealWitness<Seal, Message = Self::Message, Error = Self::Error>;
+
+    /// Converts the witness into the compact representation.
+    fn compact(&self) -> Self::Compact;
+
+    /// Restores the witness from its compact representation.
+    fn expand(compact: &Self::Compact) -> Self;
+}
+
+/// Factory of new single-use-seals, implementing `Gen(p)→l` operation, which
+/// generates a new seal basing on some seal definition data `p` (`Params`).
+pub trait GenerateSeal<Params> {
+    /// Type of the generated seals.
+    type Seal;
+
+    /// Error type that contains reasons of the seal generation failure.
+    type Error: std::error::Error;
+
+    /// Generates a new seal from the seal definition data.
+    fn gen_seal(&mut self, params: Params) -> Result<Self::Seal, Self::Error>;
+}
+
+/// Verifies a chain of seal closings, where each step closes a seal defined by
+/// the message of the previous step (like in a chain of state transitions).
+///
+/// Each step is a tuple of a seal, a witness of the seal closing and the
+/// message over which the seal was closed. The `link` function is called for
+/// each step except the first one with the message of the previous step and
+/// the seal of the current step, and must return whether the seal is defined
+/// by the message.
+///
+/// Returns the index of the first failing step within [`SealChainError`].
+pub fn verify_seal_chain<Seal, Witness: SealWitness<Seal>>(
+    steps: &[(Seal, Witness, Witness::Message)],
+    link: impl Fn(&Witness::Message, &Seal) -> bool,
+) -> Result<(), SealChainError<Witness::Error>> {
+    let mut prev = None;
+    for (step, (seal, witness, msg)) in steps.iter().enumerate() {
+        if let Some(prev) = prev {
+            if !link(prev, seal) {
+                return Err(SealChainError::BrokenLink(step));
+            }
+        }
+        witness
+            .verify_seal(seal, msg)
+            .map_err(|error| SealChainError::Verify { step, error })?;
+        prev = Some(msg);
+    }
+    Ok(())
 }
 
 /// Asynchronous version of the [`SealProtocol`] trait.
@@ -313,6 +788,24 @@ where
     /// Checks the status for a given seal in proof-of-publication medium
     async fn get_seal_status_async(&self, seal: &Seal) -> Result<SealStatus, Self::Error>;
 
+    /// Returns the current state of the medium (like its height). Function
+    /// has default implementation returning [`MediumState::Unknown`].
+    async fn medium_state_async(&self) -> Result<MediumState, Self::Error> {
+        Ok(MediumState::Unknown)
+    }
+
+    /// Checks the status for a given seal in proof-of-publication medium,
+    /// returning it together with the state of the medium at which the status
+    /// was determined. Function has default implementation calling
+    /// [`Self::get_seal_status_async`] and [`Self::medium_state_async`].
+    async fn get_seal_status_at_async(
+        &self,
+        seal: &Seal,
+    ) -> Result<(SealStatus, MediumState), Self::Error> {
+        let status = self.get_seal_status_async(seal).await?;
+        Ok((status, self.medium_state_async().await?))
+    }
+
     /// Publishes witness data to the medium. Function has default
     /// implementation doing nothing and returning
     /// [`SealMediumError::PublicationNotSupported`] error.
@@ -343,6 +836,16 @@ where
     ) -> Result<bool, SealMediumError<Self::Error>> {
         Err(SealMediumError::PublicationNotSupported)
     }
+
+    /// Returns window of the medium heights within which the witness is
+    /// considered valid. Function has default implementation doing nothing
+    /// and returning [`SealMediumError::ValidityNotSupported`] error.
+    async fn witness_validity_async(
+        &self,
+        _witness: &Self::Witness,
+    ) -> Result<ValidityWindow, SealMediumError<Self::Error>> {
+        Err(SealMediumError::ValidityNotSupported)
+    }
 }
 
 /// Adds support for the seal close operation to [`SealProtocolAsync`].
@@ -456,6 +959,54 @@ where Seal: Sync + Send
         }
         return Ok(());
     }
+
+    /// Performs batch verification of the seals, verifying all of them and
+    /// reporting all the failures.
+    ///
+    /// Unlike [`Self::verify_all_seals_async`], doesn't stop on the first
+    /// failure; the returned error lists the index of each failed seal
+    /// together with the reason of the failure.
+    async fn verify_many_seals_report_async<'seal, I>(
+        &self,
+        seals: I,
+        msg: &Self::Message,
+    ) -> Result<(), Vec<(usize, Self::Error)>>
+    where
+        I: IntoIterator<Item = &'seal Seal> + Send,
+        I::IntoIter: Send,
+        Seal: 'seal,
+        Self::Error: Send,
+    {
+        let mut failures = vec![];
+        for (index, seal) in seals.into_iter().enumerate() {
+            if let Err(err) = self.verify_seal_async(seal, msg).await {
+                failures.push((index, err));
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+/// Asynchronous version of the [`GenerateSeal`] trait.
+#[cfg(feature = "async")]
+#[async_trait]
+pub trait GenerateSealAsync<Params>
+where
+    Params: Send,
+    Self: Send + Sync,
+{
+    /// Type of the generated seals.
+    type Seal: Send;
+
+    /// Error type that contains reasons of the seal generation failure.
+    type Error: std::error::Error;
+
+    /// Generates a new seal from the seal definition data.
+    async fn gen_seal_async(&mut self, params: Params) -> Result<Self::Seal, Self::Error>;
 }
 
 /// Single-use-seal status returned by [`SealProtocol::get_seal_status`] and
@@ -469,7 +1020,13 @@ where Seal: Sync + Send
 ///   procedure to deterministically prove that a given seal is not closed (i.e.
 ///   opened), however this is not a part of the specification and we should
 ///   not rely on the existence of such possibility in all cases.
-#[derive(Clone, Copy, Debug, Display)]
+///
+/// An implementation may return [`SealStatus::Open`] only if it is able to
+/// prove that the seal was never closed as of the current medium state (for
+/// instance, being fully synchronized with a blockchain and checking the
+/// absence of any spending transaction). Otherwise it must return
+/// [`SealStatus::Undefined`] for seals which are not known to be closed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display)]
 #[display(Debug)]
 #[repr(u8)]
 pub enum SealStatus {
@@ -478,6 +1035,59 @@ pub enum SealStatus {
 
     /// The seal is closed
     Closed = 1,
+
+    /// The seal is proven to be not closed
+    Open = 2,
+}
+
+impl SealStatus {
+    /// Detects whether the status is known for sure, i.e. the seal is either
+    /// closed or proven to be open.
+    pub fn is_definitive(&self) -> bool { !matches!(self, SealStatus::Undefined) }
+}
+
+/// State of the proof-of-publication medium at which some query was
+/// performed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Display)]
+pub enum MediumState {
+    /// The medium state is not known.
+    #[default]
+    #[display("unknown")]
+    Unknown,
+
+    /// Medium height (like block height) at which the query was performed.
+    #[display("height {0}")]
+    Height(u64),
+}
+
+/// Window of the medium heights (like block heights) within which a witness
+/// is considered valid. Absent bound means the window is not limited from the
+/// respective side.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ValidityWindow {
+    /// The first medium height at which the witness becomes valid.
+    pub valid_from: Option<u64>,
+
+    /// The last medium height at which the witness is still valid.
+    pub valid_until: Option<u64>,
+}
+
+impl ValidityWindow {
+    /// Constructs validity window with the provided bounds.
+    pub fn with(valid_from: Option<u64>, valid_until: Option<u64>) -> Self {
+        Self {
+            valid_from,
+            valid_until,
+        }
+    }
+
+    /// Checks whether the medium `height` falls into the validity window.
+    pub fn contains(&self, height: u64) -> bool {
+        self.valid_from.map(|from| height >= from).unwrap_or(true) &&
+            self.valid_until
+                .map(|until| height <= until)
+                .unwrap_or(true)
+    }
 }
 
 /// Error returned by [`SealProtocol`] and `SealProtocolAsync` functions related
@@ -489,8 +1099,12 @@ pub enum SealStatus {
 /// standard error related to [`SealProtocol`] operations within
 /// [`SealMediumError::MediumAccessError`] case; the type of MediumAccessError
 /// is defined through generic argument to [`SealMediumError`].
+///
+/// The enum is non-exhaustive, since new medium capabilities may require new
+/// error cases.
 #[derive(Clone, Copy, Debug, Display, Error, From)]
 #[display(doc_comments)]
+#[non_exhaustive]
 pub enum SealMediumError<E: std::error::Error> {
     /// Can't access the publication medium
     #[from]
@@ -498,4 +1112,1000 @@ pub enum SealMediumError<E: std::error::Error> {
 
     /// Publication id is not supported
     PublicationNotSupported,
+
+    /// Witness validity window is not supported
+    ValidityNotSupported,
+
+    /// Export and import of the medium state is not supported
+    SnapshotNotSupported,
+
+    /// Witness is not published to the medium
+    WitnessNotPublished,
+
+    /// Medium height is not known
+    HeightUnknown,
+
+    /// Witness is not valid at the medium height {height}
+    WitnessOutOfWindow {
+        /// Medium height at which the witness is verified
+        height: u64,
+    },
+
+    /// Seal is closed at medium height {height} outside of its validity window
+    SealExpired {
+        /// Medium height at which the seal is closed
+        height: u64,
+    },
+
+    /// Number of seals to close ({requested}) exceeds the medium capacity
+    /// ({capacity})
+    CapacityExceeded {
+        /// Number of seals requested to be closed
+        requested: usize,
+        /// Maximal number of seals which can be closed by the medium at once
+        capacity: usize,
+    },
+}
+
+/// Error returned by [`CloseSeal::close_all_seals_indexed`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Error)]
+pub struct SealCloseError<E: std::error::Error> {
+    /// Index of the seal which caused the failure, or `None` if the failure
+    /// can't be attributed to a specific seal.
+    pub index: Option<usize>,
+
+    /// Reason of the failure.
+    pub source: E,
+}
+
+impl<E: std::error::Error> std::fmt::Display for SealCloseError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "unable to close seal #{index}. {}", self.source),
+            None => write!(f, "unable to close seals. {}", self.source),
+        }
+    }
+}
+
+/// Error returned by [`CloseSeal::close_and_publish`].
+#[derive(Clone, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ClosePublishError<W: Debug, E: std::error::Error> {
+    /// unable to close the seal. {0}
+    Close(E),
+
+    /// the seal was closed, but the witness can't be published. {error}
+    Publish {
+        /// Witness produced by closing the seal, which may be published later.
+        witness: W,
+
+        /// Reason of the publication failure.
+        error: SealMediumError<E>,
+    },
+}
+
+/// Error returned by [`SealWitness::verify_seal_from_bytes`].
+#[cfg(feature = "strict")]
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum VerifyBytesError<E: std::error::Error> {
+    /// unable to decode seal witness. {0}
+    #[from]
+    Decode(strict_encoding::DeserializeError),
+
+    /// invalid seal witness. {0}
+    Verify(E),
+}
+
+/// Error returned by [`verify_seal_chain`].
+#[derive(Clone, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum SealChainError<E: std::error::Error> {
+    /// seal at step {0} is not defined by the message of the previous step.
+    BrokenLink(usize),
+
+    /// seal at step {step} is not closed over the message. {error}
+    Verify {
+        /// Index of the failing step.
+        step: usize,
+
+        /// Reason of the verification failure.
+        error: E,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+
+    #[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+    #[display(doc_comments)]
+    enum Error {
+        /// seal {0} is already closed.
+        AlreadyClosed(u8),
+
+        /// seal {0} is not closed over the message.
+        NotClosed(u8),
+
+        /// seal {0} is revoked.
+        Revoked(u8),
+
+        /// seal {0} definition is malformed.
+        Malformed(u8),
+
+        /// witnesses can't be aggregated.
+        Conflict,
+
+        /// publication medium is not available.
+        Unavailable,
+
+        /// medium state data are invalid.
+        InvalidState,
+
+        /// {0} seals exceed the medium capacity.
+        CapacityExceeded(usize),
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct Witness {
+        seals: Vec<u8>,
+        msg: u64,
+        nonce: [u8; 32],
+    }
+
+    #[derive(Default)]
+    struct Medium {
+        closed: BTreeMap<u8, u64>,
+        published: Vec<Witness>,
+        offline: bool,
+        entropy: u8,
+        validity: BTreeMap<u64, ValidityWindow>,
+        revoked: BTreeSet<u8>,
+        capacity: Option<usize>,
+        seal_validity: BTreeMap<u8, ValidityWindow>,
+        watchers: BTreeMap<u8, Vec<Box<dyn Fn(SealStatus)>>>,
+    }
+
+    impl Medium {
+        fn fresh_nonce(&mut self) -> [u8; 32] {
+            self.entropy = self.entropy.wrapping_add(1);
+            [self.entropy; 32]
+        }
+
+        fn close_with<'seal>(
+            &self,
+            seals: impl IntoIterator<Item = &'seal u8>,
+            over: &u64,
+            nonce: [u8; 32],
+        ) -> Result<Witness, Error> {
+            let seals = seals.into_iter().copied().collect::<Vec<_>>();
+            if self
+                .capacity
+                .map_or(false, |capacity| seals.len() > capacity)
+            {
+                return Err(Error::CapacityExceeded(seals.len()));
+            }
+            if let Some(seal) = seals.iter().find(|seal| self.revoked.contains(*seal)) {
+                return Err(Error::Revoked(*seal));
+            }
+            if let Some(seal) = seals.iter().find(|seal| self.closed.contains_key(*seal)) {
+                return Err(Error::AlreadyClosed(*seal));
+            }
+            Ok(Witness {
+                seals,
+                msg: *over,
+                nonce,
+            })
+        }
+    }
+
+    impl SealProtocol<u8> for Medium {
+        type Witness = Witness;
+        type Message = u64;
+        type PublicationId = usize;
+        type Error = Error;
+
+        fn get_seal_status(&self, seal: &u8) -> Result<SealStatus, Error> {
+            Ok(match self.closed.contains_key(seal) {
+                true => SealStatus::Closed,
+                false => SealStatus::Undefined,
+            })
+        }
+
+        fn medium_state(&self) -> Result<MediumState, Error> {
+            Ok(MediumState::Height(self.published.len() as u64))
+        }
+
+        fn publish_witness(&mut self, witness: &Witness) -> Result<usize, SealMediumError<Error>> {
+            if self.offline {
+                return Err(Error::Unavailable.into());
+            }
+            for seal in &witness.seals {
+                self.closed.insert(*seal, witness.msg);
+                for cb in self.watchers.get(seal).into_iter().flatten() {
+                    cb(SealStatus::Closed);
+                }
+            }
+            self.published.push(witness.clone());
+            Ok(self.published.len() - 1)
+        }
+
+        fn get_witness_publication_height(
+            &self,
+            witness: &Witness,
+        ) -> Result<Option<u64>, SealMediumError<Error>> {
+            // Each published witness increases the medium height by one
+            Ok(self
+                .published
+                .iter()
+                .position(|published| published == witness)
+                .map(|pos| pos as u64 + 1))
+        }
+
+        fn remaining_capacity(&self) -> Result<Option<usize>, Error> { Ok(self.capacity) }
+
+        fn witness_validity(
+            &self,
+            witness: &Witness,
+        ) -> Result<ValidityWindow, SealMediumError<Error>> {
+            Ok(self.validity.get(&witness.msg).copied().unwrap_or_default())
+        }
+
+        fn seal_validity(&self, seal: &u8) -> Result<Option<ValidityWindow>, Error> {
+            Ok(self.seal_validity.get(seal).copied())
+        }
+
+        fn validate_seal_definition(&self, seal: &u8) -> Result<(), Error> {
+            match *seal {
+                0 => Err(Error::Malformed(0)),
+                _ => Ok(()),
+            }
+        }
+
+        fn export_state(&self) -> Result<Vec<u8>, SealMediumError<Error>> {
+            Ok(testmock::export_closed(&self.closed))
+        }
+
+        fn import_state(&mut self, data: &[u8]) -> Result<(), SealMediumError<Error>> {
+            self.closed = testmock::import_closed(data).map_err(|_| Error::InvalidState)?;
+            Ok(())
+        }
+    }
+
+    impl CloseSeal<u8> for Medium {
+        fn close_seal(&mut self, seal: &u8, over: &u64) -> Result<Witness, Error> {
+            let nonce = self.fresh_nonce();
+            self.close_seal_deterministic(seal, over, nonce)
+        }
+
+        fn close_all_seals<'seal>(
+            &mut self,
+            seals: impl IntoIterator<Item = &'seal u8>,
+            over: &u64,
+        ) -> Result<Witness, Error>
+        where
+            u8: 'seal,
+        {
+            let nonce = self.fresh_nonce();
+            self.close_with(seals, over, nonce)
+        }
+
+        fn check_closable(&self, seal: &u8) -> Result<(), Error> {
+            if self.revoked.contains(seal) {
+                return Err(Error::Revoked(*seal));
+            }
+            if self.closed.contains_key(seal) {
+                return Err(Error::AlreadyClosed(*seal));
+            }
+            Ok(())
+        }
+    }
+
+    impl CloseSealDeterministic<u8> for Medium {
+        fn close_seal_deterministic(
+            &mut self,
+            seal: &u8,
+            over: &u64,
+            nonce: [u8; 32],
+        ) -> Result<Witness, Error> {
+            self.close_with([seal], over, nonce)
+        }
+    }
+
+    impl RevokeSeal<u8> for Medium {
+        fn revoke_seal(&mut self, seal: &u8) -> Result<(), Error> {
+            self.revoked.insert(*seal);
+            Ok(())
+        }
+
+        fn is_revoked(&self, seal: &u8) -> Result<bool, Error> { Ok(self.revoked.contains(seal)) }
+    }
+
+    impl WatchSeal<u8> for Medium {
+        fn watch_seal(&mut self, seal: u8, cb: Box<dyn Fn(SealStatus)>) -> Result<(), Error> {
+            self.watchers.entry(seal).or_default().push(cb);
+            Ok(())
+        }
+    }
+
+    impl SealWitness<u8> for Witness {
+        type Message = u64;
+        type Error = Error;
+
+        fn verify_seal(&self, seal: &u8, msg: &u64) -> Result<(), Error> {
+            if !self.seals.contains(seal) || self.msg != *msg {
+                return Err(Error::NotClosed(*seal));
+            }
+            Ok(())
+        }
+    }
+
+    impl AggregateWitness<u8> for Witness {
+        fn aggregate(witnesses: Vec<Self>) -> Result<Self, Error> {
+            let mut iter = witnesses.into_iter();
+            let mut aggregate = iter.next().ok_or(Error::Conflict)?;
+            for witness in iter {
+                if witness.msg != aggregate.msg {
+                    return Err(Error::Conflict);
+                }
+                for seal in witness.seals {
+                    if aggregate.seals.contains(&seal) {
+                        return Err(Error::AlreadyClosed(seal));
+                    }
+                    aggregate.seals.push(seal);
+                }
+            }
+            Ok(aggregate)
+        }
+    }
+
+    /// Witness storing closed seals as ranges of consecutive seal numbers.
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct CompactMockWitness {
+        ranges: Vec<(u8, u8)>,
+        msg: u64,
+        nonce: [u8; 32],
+    }
+
+    impl SealWitness<u8> for CompactMockWitness {
+        type Message = u64;
+        type Error = Error;
+
+        fn verify_seal(&self, seal: &u8, msg: &u64) -> Result<(), Error> {
+            if self.msg != *msg ||
+                !self
+                    .ranges
+                    .iter()
+                    .any(|(from, to)| (from..=to).contains(&seal))
+            {
+                return Err(Error::NotClosed(*seal));
+            }
+            Ok(())
+        }
+    }
+
+    impl CompactWitness<u8> for Witness {
+        type Compact = CompactMockWitness;
+
+        fn compact(&self) -> CompactMockWitness {
+            let mut ranges = Vec::<(u8, u8)>::new();
+            for seal in &self.seals {
+                match ranges.last_mut() {
+                    Some((_, to)) if to.checked_add(1) == Some(*seal) => *to = *seal,
+                    _ => ranges.push((*seal, *seal)),
+                }
+            }
+            CompactMockWitness {
+                ranges,
+                msg: self.msg,
+                nonce: self.nonce,
+            }
+        }
+
+        fn expand(compact: &CompactMockWitness) -> Self {
+            Witness {
+                seals: compact
+                    .ranges
+                    .iter()
+                    .flat_map(|(from, to)| *from..=*to)
+                    .collect(),
+                msg: compact.msg,
+                nonce: compact.nonce,
+            }
+        }
+    }
+
+    #[test]
+    fn close_and_publish() {
+        let mut medium = Medium::default();
+        let (witness, id) = medium.close_and_publish(&1, &0xcafe).unwrap();
+        assert_eq!(id, 0);
+        assert_eq!(medium.published, vec![witness.clone()]);
+        assert!(matches!(medium.get_seal_status(&1), Ok(SealStatus::Closed)));
+        assert_eq!(witness.verify_seal(&1, &0xcafe), Ok(()));
+        assert_eq!(witness.verify_seal(&1, &0xbeef), Err(Error::NotClosed(1)));
+    }
+
+    #[test]
+    fn close_and_publish_failure() {
+        let mut medium = Medium {
+            offline: true,
+            ..Medium::default()
+        };
+        let err = medium.close_and_publish(&1, &0xcafe).unwrap_err();
+        let (witness, error) = match err {
+            ClosePublishError::Publish { witness, error } => (witness, error),
+            err => panic!("unexpected error {err}"),
+        };
+        assert!(matches!(error, SealMediumError::MediumAccessError(Error::Unavailable)));
+        assert!(matches!(medium.get_seal_status(&1), Ok(SealStatus::Undefined)));
+        assert_eq!(witness.verify_seal(&1, &0xcafe), Ok(()));
+
+        medium.offline = false;
+        assert_eq!(medium.publish_witness(&witness).unwrap(), 0);
+        assert!(matches!(medium.get_seal_status(&1), Ok(SealStatus::Closed)));
+
+        let err = medium.close_and_publish(&1, &0xbeef).unwrap_err();
+        assert!(matches!(err, ClosePublishError::Close(Error::AlreadyClosed(1))));
+    }
+
+    #[test]
+    fn close_seal_deterministic() {
+        let mut medium = Medium::default();
+        let witness1 = medium
+            .close_seal_deterministic(&1, &0xcafe, [7; 32])
+            .unwrap();
+        let witness2 = medium
+            .close_seal_deterministic(&1, &0xcafe, [7; 32])
+            .unwrap();
+        assert_eq!(witness1, witness2);
+        assert_eq!(witness1.verify_seal(&1, &0xcafe), Ok(()));
+
+        let witness3 = medium
+            .close_seal_deterministic(&1, &0xcafe, [8; 32])
+            .unwrap();
+        assert_ne!(witness1, witness3);
+
+        let random1 = medium.close_seal(&1, &0xcafe).unwrap();
+        let random2 = medium.close_seal(&1, &0xcafe).unwrap();
+        assert_ne!(random1, random2);
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    fn verify_seal_from_bytes() {
+        use strict_encoding::{
+            StrictDecode, StrictDeserialize, StrictDumb, StrictEncode, StrictSerialize, StrictType,
+        };
+
+        #[derive(Clone, PartialEq, Eq, Debug, Default)]
+        #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "SingleUseSealsTest")]
+        struct SingleWitness {
+            seal: u8,
+            msg: u64,
+        }
+        impl StrictSerialize for SingleWitness {}
+        impl StrictDeserialize for SingleWitness {}
+
+        impl SealWitness<u8> for SingleWitness {
+            type Message = u64;
+            type Error = Error;
+
+            fn verify_seal(&self, seal: &u8, msg: &u64) -> Result<(), Error> {
+                if self.seal != *seal || self.msg != *msg {
+                    return Err(Error::NotClosed(*seal));
+                }
+                Ok(())
+            }
+        }
+
+        let witness = SingleWitness {
+            seal: 1,
+            msg: 0xcafe,
+        };
+        let bytes = witness.to_strict_serialized::<0xFF>().unwrap();
+        assert!(SingleWitness::verify_seal_from_bytes(&bytes, &1, &0xcafe).is_ok());
+        assert!(matches!(
+            SingleWitness::verify_seal_from_bytes(&bytes, &1, &0xbeef),
+            Err(VerifyBytesError::Verify(Error::NotClosed(1)))
+        ));
+        assert!(matches!(
+            SingleWitness::verify_seal_from_bytes(&bytes[..4], &1, &0xcafe),
+            Err(VerifyBytesError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn witness_validity() {
+        let mut medium = Medium::default();
+        medium
+            .validity
+            .insert(0xcafe, ValidityWindow::with(Some(100), Some(200)));
+
+        let witness = medium.close_seal(&1, &0xcafe).unwrap();
+        let window = medium.witness_validity(&witness).unwrap();
+        assert!(window.contains(100));
+        assert!(window.contains(200));
+        assert!(!window.contains(99));
+        assert!(!window.contains(201));
+
+        let witness = medium.close_seal(&2, &0xbeef).unwrap();
+        let window = medium.witness_validity(&witness).unwrap();
+        assert_eq!(window, ValidityWindow::default());
+        assert!(window.contains(0));
+        assert!(window.contains(u64::MAX));
+    }
+
+    #[test]
+    fn verify_seal_fresh() {
+        let mut medium = Medium::default();
+        medium
+            .validity
+            .insert(0xcafe, ValidityWindow::with(Some(1), Some(2)));
+
+        let witness = medium.close_seal(&1, &0xcafe).unwrap();
+        assert!(matches!(
+            medium.verify_seal_fresh(&witness, &1, &0xcafe),
+            Err(SealMediumError::WitnessOutOfWindow { height: 0 })
+        ));
+        medium.publish_witness(&witness).unwrap();
+        assert!(medium.verify_seal_fresh(&witness, &1, &0xcafe).is_ok());
+        assert!(matches!(
+            medium.verify_seal_fresh(&witness, &1, &0xbeef),
+            Err(SealMediumError::MediumAccessError(Error::NotClosed(1)))
+        ));
+
+        for seal in 2..4 {
+            let other = medium.close_seal(&seal, &0xbeef).unwrap();
+            medium.publish_witness(&other).unwrap();
+        }
+        assert!(matches!(
+            medium.verify_seal_fresh(&witness, &1, &0xcafe),
+            Err(SealMediumError::WitnessOutOfWindow { height: 3 })
+        ));
+
+        // Mediums with validity windows, but without the current height
+        struct NoHeight(Medium);
+
+        impl SealProtocol<u8> for NoHeight {
+            type Witness = Witness;
+            type Message = u64;
+            type PublicationId = usize;
+            type Error = Error;
+
+            fn get_seal_status(&self, seal: &u8) -> Result<SealStatus, Error> {
+                self.0.get_seal_status(seal)
+            }
+
+            fn witness_validity(
+                &self,
+                witness: &Witness,
+            ) -> Result<ValidityWindow, SealMediumError<Error>> {
+                self.0.witness_validity(witness)
+            }
+        }
+
+        let no_height = NoHeight(medium);
+        assert!(matches!(
+            no_height.verify_seal_fresh(&witness, &1, &0xcafe),
+            Err(SealMediumError::HeightUnknown)
+        ));
+
+        // Mediums without validity windows verify only the seal closing
+        let mut memory = testmock::MemorySeals::<u8, u64>::new();
+        let witness = memory.close_seal(&1, &0xcafe).unwrap();
+        assert!(memory.verify_seal_fresh(&witness, &1, &0xcafe).is_ok());
+    }
+
+    #[test]
+    fn get_seal_status_at() {
+        let mut medium = Medium::default();
+        assert!(matches!(
+            medium.get_seal_status_at(&1),
+            Ok((SealStatus::Undefined, MediumState::Height(0)))
+        ));
+        medium.close_and_publish(&1, &0xcafe).unwrap();
+        medium.close_and_publish(&2, &0xcafe).unwrap();
+        assert!(matches!(
+            medium.get_seal_status_at(&1),
+            Ok((SealStatus::Closed, MediumState::Height(2)))
+        ));
+    }
+
+    #[test]
+    fn revoke_seal() {
+        let mut medium = Medium::default();
+        assert_eq!(medium.is_revoked(&1), Ok(false));
+        medium.revoke_seal(&1).unwrap();
+        assert_eq!(medium.is_revoked(&1), Ok(true));
+        assert_eq!(medium.is_revoked(&2), Ok(false));
+
+        assert_eq!(medium.close_seal(&1, &0xcafe).unwrap_err(), Error::Revoked(1));
+        assert_eq!(medium.close_all_seals([&2, &1], &0xcafe).unwrap_err(), Error::Revoked(1));
+        assert!(medium.close_seal(&2, &0xcafe).is_ok());
+    }
+
+    #[test]
+    fn close_all_seals_checked() {
+        let mut medium = Medium {
+            capacity: Some(2),
+            ..Medium::default()
+        };
+        let witness = medium.close_all_seals_checked([&1, &2], &0xcafe).unwrap();
+        assert_eq!(witness.verify_many_seals([&1, &2], &0xcafe), Ok(()));
+
+        let err = medium
+            .close_all_seals_checked([&1, &2, &3], &0xcafe)
+            .unwrap_err();
+        assert!(matches!(err, SealMediumError::CapacityExceeded {
+            requested: 3,
+            capacity: 2
+        }));
+        assert_eq!(medium.close_all_seals([&1, &2, &3], &0xcafe), Err(Error::CapacityExceeded(3)));
+        assert!(medium.close_all_seals([&1, &2], &0xcafe).is_ok());
+
+        medium.capacity = None;
+        assert!(medium
+            .close_all_seals_checked([&1, &2, &3], &0xcafe)
+            .is_ok());
+    }
+
+    #[test]
+    fn aggregate_witness() {
+        let mut alice = Medium::default();
+        let mut bob = Medium::default();
+        let witness1 = alice.close_all_seals([&1, &2], &0xcafe).unwrap();
+        let witness2 = bob.close_seal(&3, &0xcafe).unwrap();
+
+        let aggregate = Witness::aggregate(vec![witness1.clone(), witness2]).unwrap();
+        assert_eq!(aggregate.verify_many_seals([&1, &2, &3], &0xcafe), Ok(()));
+
+        let overlapping = bob.close_seal(&2, &0xcafe).unwrap();
+        assert_eq!(
+            Witness::aggregate(vec![witness1.clone(), overlapping]).unwrap_err(),
+            Error::AlreadyClosed(2)
+        );
+        let other_msg = bob.close_seal(&4, &0xbeef).unwrap();
+        assert_eq!(Witness::aggregate(vec![witness1, other_msg]).unwrap_err(), Error::Conflict);
+        assert_eq!(Witness::aggregate(vec![]).unwrap_err(), Error::Conflict);
+    }
+
+    #[cfg(feature = "commit")]
+    #[test]
+    fn structured_message() {
+        use commit_verify::mpc::{Commitment, MerkleTree, Message, MultiSource, ProtocolId};
+        use commit_verify::{CommitId, TryCommitVerify};
+
+        struct CommitmentWitness {
+            seal: u8,
+            msg: Commitment,
+        }
+
+        impl SealWitness<u8> for CommitmentWitness {
+            type Message = Commitment;
+            type Error = Error;
+
+            fn verify_seal(&self, seal: &u8, msg: &Commitment) -> Result<(), Error> {
+                if self.seal != *seal || self.msg != *msg {
+                    return Err(Error::NotClosed(*seal));
+                }
+                Ok(())
+            }
+        }
+
+        let mut source = MultiSource::with_static_entropy(0xcafe);
+        source
+            .messages
+            .insert(ProtocolId::from([1; 32]), Message::from([2; 32]))
+            .unwrap();
+        let tree = MerkleTree::try_commit(&source).unwrap();
+        source
+            .messages
+            .insert(ProtocolId::from([3; 32]), Message::from([4; 32]))
+            .unwrap();
+        let other = MerkleTree::try_commit(&source).unwrap();
+
+        let witness = CommitmentWitness {
+            seal: 1,
+            msg: tree.commit_id(),
+        };
+        assert_eq!(witness.verify_seal_committed(&1, &tree), Ok(()));
+        assert_eq!(witness.verify_seal(&1, &tree.commit_id()), Ok(()));
+        assert_eq!(witness.verify_seal_committed(&1, &other), Err(Error::NotClosed(1)));
+        assert_eq!(witness.verify_seal_committed(&2, &tree), Err(Error::NotClosed(2)));
+    }
+
+    #[test]
+    fn verify_seal_chain() {
+        let mut medium = Medium::default();
+        let link = |msg: &u64, seal: &u8| *msg == *seal as u64;
+        let mut steps = vec![];
+        for (seal, msg) in [(1u8, 2u64), (2, 3), (3, 4)] {
+            let witness = medium.close_seal(&seal, &msg).unwrap();
+            medium.publish_witness(&witness).unwrap();
+            steps.push((seal, witness, msg));
+        }
+        assert!(super::verify_seal_chain(&steps, link).is_ok());
+
+        let mut broken = steps.clone();
+        broken[2].0 = 5;
+        assert!(matches!(
+            super::verify_seal_chain(&broken, link),
+            Err(SealChainError::BrokenLink(2))
+        ));
+
+        let mut broken = steps;
+        broken[1].2 = 5;
+        assert!(matches!(
+            super::verify_seal_chain(&broken, link),
+            Err(SealChainError::Verify {
+                step: 1,
+                error: Error::NotClosed(2)
+            })
+        ));
+    }
+
+    #[test]
+    fn watch_seal() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let mut medium = Medium::default();
+        let fired = Rc::new(Cell::new(None));
+        let observed = fired.clone();
+        medium
+            .watch_seal(1, Box::new(move |status| observed.set(Some(status))))
+            .unwrap();
+
+        let witness = medium.close_seal(&2, &0xcafe).unwrap();
+        medium.publish_witness(&witness).unwrap();
+        assert!(fired.get().is_none());
+
+        let witness = medium.close_seal(&1, &0xcafe).unwrap();
+        assert!(fired.get().is_none());
+        medium.publish_witness(&witness).unwrap();
+        assert!(matches!(fired.get(), Some(SealStatus::Closed)));
+    }
+
+    #[test]
+    #[cfg(feature = "strict")]
+    fn close_all_seals_ordered() {
+        let witness1 = Medium::default()
+            .close_all_seals_ordered([&1, &2, &3], &0xcafe)
+            .unwrap();
+        let witness2 = Medium::default()
+            .close_all_seals_ordered([&3, &1, &2], &0xcafe)
+            .unwrap();
+        assert_eq!(witness1, witness2);
+        assert_eq!(witness1.seals, vec![1, 2, 3]);
+
+        let witness3 = Medium::default()
+            .close_all_seals([&3, &1, &2], &0xcafe)
+            .unwrap();
+        assert_ne!(witness1, witness3);
+    }
+
+    #[test]
+    fn compact_witness() {
+        let mut medium = Medium::default();
+        let seals = (1..=100).chain(120..=150).collect::<Vec<u8>>();
+        let witness = medium.close_all_seals(&seals, &0xcafe).unwrap();
+
+        let compact = witness.compact();
+        assert_eq!(compact.ranges, vec![(1, 100), (120, 150)]);
+        assert_eq!(compact.verify_many_seals(&seals, &0xcafe), Ok(()));
+        assert_eq!(compact.verify_seal(&110, &0xcafe), Err(Error::NotClosed(110)));
+        assert_eq!(compact.verify_seal(&1, &0xbeef), Err(Error::NotClosed(1)));
+        assert_eq!(Witness::expand(&compact), witness);
+    }
+
+    #[test]
+    fn verify_many_seals_report() {
+        let mut medium = Medium::default();
+        let witness = medium.close_all_seals([&1, &2, &3], &0xcafe).unwrap();
+
+        assert_eq!(witness.verify_many_seals_report([&1, &2, &3], &0xcafe), Ok(()));
+        assert_eq!(
+            witness.verify_many_seals_report([&4, &1, &5, &3], &0xcafe),
+            Err(vec![(0, Error::NotClosed(4)), (2, Error::NotClosed(5))])
+        );
+        assert_eq!(witness.verify_many_seals([&4, &1, &5, &3], &0xcafe), Err(Error::NotClosed(4)));
+    }
+
+    #[test]
+    fn seal_status() {
+        assert!(!SealStatus::Undefined.is_definitive());
+        assert!(SealStatus::Closed.is_definitive());
+        assert!(SealStatus::Open.is_definitive());
+        assert_eq!(SealStatus::Undefined as u8, 0);
+        assert_eq!(SealStatus::Closed as u8, 1);
+        assert_eq!(SealStatus::Open as u8, 2);
+    }
+
+    #[test]
+    fn export_import_state() {
+        let mut medium = Medium::default();
+        let witness = medium.close_all_seals([&1, &2], &0xcafe).unwrap();
+        medium.publish_witness(&witness).unwrap();
+        let witness = medium.close_seal(&5, &0xbeef).unwrap();
+        medium.publish_witness(&witness).unwrap();
+
+        let data = medium.export_state().unwrap();
+        let mut restored = Medium::default();
+        restored.import_state(&data).unwrap();
+        for seal in 0..8 {
+            assert_eq!(restored.get_seal_status(&seal), medium.get_seal_status(&seal));
+        }
+        assert_eq!(restored.closed, medium.closed);
+
+        assert!(matches!(
+            restored.import_state(&data[1..]),
+            Err(SealMediumError::MediumAccessError(Error::InvalidState))
+        ));
+    }
+
+    #[test]
+    fn gen_seal() {
+        #[derive(Default)]
+        struct Factory {
+            used: BTreeSet<u8>,
+        }
+
+        impl GenerateSeal<u8> for Factory {
+            type Seal = u8;
+            type Error = Error;
+
+            fn gen_seal(&mut self, base: u8) -> Result<u8, Error> {
+                let seal = (base..=u8::MAX)
+                    .find(|seal| !self.used.contains(seal))
+                    .ok_or(Error::Unavailable)?;
+                self.used.insert(seal);
+                Ok(seal)
+            }
+        }
+
+        let mut factory = Factory::default();
+        assert_eq!(factory.gen_seal(1), Ok(1));
+        assert_eq!(factory.gen_seal(1), Ok(2));
+        assert_eq!(factory.gen_seal(0xFF), Ok(0xFF));
+        assert_eq!(factory.gen_seal(0xFF), Err(Error::Unavailable));
+    }
+
+    #[test]
+    fn seal_validity() {
+        let mut medium = Medium::default();
+        medium
+            .seal_validity
+            .insert(1, ValidityWindow::with(Some(1), Some(2)));
+        medium
+            .seal_validity
+            .insert(2, ValidityWindow::with(None, Some(0)));
+
+        let witness = medium.close_all_seals([&1, &2, &3], &0xcafe).unwrap();
+        medium.publish_witness(&witness).unwrap();
+        assert!(medium
+            .verify_seal_within_window(&witness, &1, &0xcafe)
+            .is_ok());
+        assert!(medium
+            .verify_seal_within_window(&witness, &3, &0xcafe)
+            .is_ok());
+        assert!(matches!(
+            medium.verify_seal_within_window(&witness, &2, &0xcafe),
+            Err(SealMediumError::SealExpired { height: 1 })
+        ));
+        assert!(matches!(
+            medium.verify_seal_within_window(&witness, &1, &0xbeef),
+            Err(SealMediumError::MediumAccessError(Error::NotClosed(1)))
+        ));
+
+        let unpublished = medium.close_seal(&4, &0xcafe).unwrap();
+        medium.seal_validity.insert(4, ValidityWindow::default());
+        assert!(matches!(
+            medium.verify_seal_within_window(&unpublished, &4, &0xcafe),
+            Err(SealMediumError::WitnessNotPublished)
+        ));
+    }
+
+    #[test]
+    fn seal_validity_after_window() {
+        let mut medium = Medium::default();
+        medium
+            .seal_validity
+            .insert(1, ValidityWindow::with(Some(1), Some(1)));
+
+        let witness = medium.close_seal(&1, &0xcafe).unwrap();
+        medium.publish_witness(&witness).unwrap();
+        for seal in 2..5 {
+            let other = medium.close_seal(&seal, &0xbeef).unwrap();
+            medium.publish_witness(&other).unwrap();
+        }
+        assert!(matches!(medium.get_seal_status_at(&1), Ok((_, MediumState::Height(4)))));
+        assert!(medium
+            .verify_seal_within_window(&witness, &1, &0xcafe)
+            .is_ok());
+    }
+
+    #[test]
+    fn close_all_seals_indexed() {
+        let mut medium = Medium::default();
+        let witness = medium.close_seal(&3, &0xcafe).unwrap();
+        medium.publish_witness(&witness).unwrap();
+        medium.revoke_seal(&5).unwrap();
+
+        assert!(medium.close_all_seals_indexed([&1, &2], &0xcafe).is_ok());
+        assert_eq!(
+            medium.close_all_seals_indexed([&1, &2, &3, &4], &0xcafe),
+            Err(SealCloseError {
+                index: Some(2),
+                source: Error::AlreadyClosed(3)
+            })
+        );
+        assert_eq!(
+            medium.close_all_seals_indexed([&1, &5, &3], &0xcafe),
+            Err(SealCloseError {
+                index: Some(1),
+                source: Error::Revoked(5)
+            })
+        );
+    }
+
+    #[test]
+    fn close_all_seals_indexed_unchecked() {
+        // Medium relying on the default `check_closable`
+        struct Unchecked(Medium);
+
+        impl SealProtocol<u8> for Unchecked {
+            type Witness = Witness;
+            type Message = u64;
+            type PublicationId = usize;
+            type Error = Error;
+
+            fn get_seal_status(&self, seal: &u8) -> Result<SealStatus, Error> {
+                self.0.get_seal_status(seal)
+            }
+        }
+
+        impl CloseSeal<u8> for Unchecked {
+            fn close_seal(&mut self, seal: &u8, over: &u64) -> Result<Witness, Error> {
+                self.0.close_seal(seal, over)
+            }
+
+            fn close_all_seals<'seal>(
+                &mut self,
+                seals: impl IntoIterator<Item = &'seal u8>,
+                over: &u64,
+            ) -> Result<Witness, Error>
+            where
+                u8: 'seal,
+            {
+                self.0.close_all_seals(seals, over)
+            }
+        }
+
+        let mut medium = Unchecked(Medium::default());
+        medium.0.revoke_seal(&5).unwrap();
+
+        let err = medium
+            .close_all_seals_indexed([&1, &5, &3], &0xcafe)
+            .unwrap_err();
+        assert_eq!(err, SealCloseError {
+            index: None,
+            source: Error::Revoked(5)
+        });
+        assert_eq!(err.to_string(), "unable to close seals. seal 5 is revoked.");
+        assert!(medium.close_all_seals_indexed([&1, &3], &0xcafe).is_ok());
+    }
+
+    #[test]
+    fn validate_seal_definition() {
+        let medium = Medium::default();
+        assert_eq!(medium.validate_seal_definition(&1), Ok(()));
+        assert_eq!(medium.validate_seal_definition(&0), Err(Error::Malformed(0)));
+
+        let memory = testmock::MemorySeals::<u8, u64>::new();
+        assert_eq!(memory.validate_seal_definition(&0), Ok(()));
+    }
 }
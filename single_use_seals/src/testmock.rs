@@ -0,0 +1,300 @@
+// Client-side-validation foundation libraries.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-memory reference implementation of the single-use-seals, which can be
+//! used for testing code generic over [`SealProtocol`] without a real
+//! proof-of-publication medium.
+
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(any(test, feature = "strict"))]
+use std::hash::Hash;
+#[cfg(any(test, feature = "strict"))]
+use std::io;
+
+#[cfg(any(test, feature = "strict"))]
+use amplify::confinement::{Confined, U32};
+#[cfg(any(test, feature = "strict"))]
+use strict_encoding::{
+    DecodeError, StreamReader, StreamWriter, StrictDecode, StrictDumb, StrictEncode,
+};
+
+use crate::{CloseSeal, SealProtocol, SealStatus, SealWitness};
+
+/// Strict-encoded representation of the set of closed seals, used for the
+/// medium state snapshots.
+#[cfg(any(test, feature = "strict"))]
+type ClosedSeals<Seal, Msg> = Confined<BTreeMap<Seal, Msg>, 0, U32>;
+
+/// Strict-encodes the map of closed seals to the messages they are closed
+/// over.
+#[cfg(any(test, feature = "strict"))]
+pub(crate) fn export_closed<Seal, Msg>(closed: &BTreeMap<Seal, Msg>) -> Vec<u8>
+where
+    Seal: Ord + Hash + Clone + StrictEncode + StrictDumb,
+    Msg: Clone + StrictEncode + StrictDumb,
+{
+    let closed = ClosedSeals::try_from(closed.clone()).expect("more than 2^32 closed seals");
+    let mut data = vec![];
+    let writer = StreamWriter::new::<U32>(&mut data);
+    closed
+        .strict_write(writer)
+        .expect("in-memory writer doesn't fail");
+    data
+}
+
+/// Decodes the map of closed seals previously encoded with [`export_closed`],
+/// requiring all the provided data to be consumed.
+#[cfg(any(test, feature = "strict"))]
+pub(crate) fn import_closed<Seal, Msg>(data: &[u8]) -> Result<BTreeMap<Seal, Msg>, DecodeError>
+where
+    Seal: Ord + Hash + StrictDecode,
+    Msg: StrictDecode,
+{
+    let mut cursor = io::Cursor::new(data);
+    let closed = ClosedSeals::<Seal, Msg>::strict_read(StreamReader::new::<U32>(&mut cursor))?;
+    if cursor.position() as usize != data.len() {
+        return Err(DecodeError::DataIntegrityError(
+            "trailing data after the medium state".to_owned(),
+        ));
+    }
+    Ok(closed.into_iter().collect())
+}
+
+/// Errors of the in-memory seal medium.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum MemorySealError {
+    /// seal is already closed.
+    AlreadyClosed,
+
+    /// seal is not closed over the message.
+    NotClosed,
+
+    /// medium state data are invalid.
+    InvalidState,
+}
+
+/// Witness produced by [`MemorySeals`], listing the closed seals and the
+/// message they are closed over.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MemoryWitness<Seal: Ord, Msg> {
+    /// Seals closed by the witness.
+    pub seals: BTreeSet<Seal>,
+
+    /// Message over which the seals are closed.
+    pub msg: Msg,
+}
+
+impl<Seal: Ord, Msg: Eq> SealWitness<Seal> for MemoryWitness<Seal, Msg> {
+    type Message = Msg;
+    type Error = MemorySealError;
+
+    fn verify_seal(&self, seal: &Seal, msg: &Msg) -> Result<(), MemorySealError> {
+        if !self.seals.contains(seal) || self.msg != *msg {
+            return Err(MemorySealError::NotClosed);
+        }
+        Ok(())
+    }
+}
+
+/// In-memory seal medium, where closing a seal immediately records the message
+/// it was closed over.
+///
+/// Since the medium knows all closed seals, it reports seals which were not
+/// closed as [`SealStatus::Open`].
+///
+/// With `strict` feature, the medium state may be exported and imported with
+/// [`MemorySeals::export_closed_seals`] and
+/// [`MemorySeals::import_closed_seals`] for the seal and message types
+/// supporting strict encoding. The state is the strict-encoded map of closed
+/// seals to the messages they are closed over. Since the rest of the mock
+/// doesn't require strict encoding, [`SealProtocol::export_state`] and
+/// [`SealProtocol::import_state`] are not supported.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MemorySeals<Seal: Ord, Msg> {
+    closed: BTreeMap<Seal, Msg>,
+}
+
+impl<Seal: Ord, Msg> Default for MemorySeals<Seal, Msg> {
+    fn default() -> Self {
+        MemorySeals {
+            closed: BTreeMap::new(),
+        }
+    }
+}
+
+impl<Seal: Ord, Msg> MemorySeals<Seal, Msg> {
+    /// Constructs medium without closed seals.
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns message over which the seal was closed, if any.
+    pub fn closed_over(&self, seal: &Seal) -> Option<&Msg> { self.closed.get(seal) }
+}
+
+#[cfg(any(test, feature = "strict"))]
+impl<Seal, Msg> MemorySeals<Seal, Msg>
+where
+    Seal: Ord + Hash + Clone + StrictEncode + StrictDecode + StrictDumb,
+    Msg: Clone + StrictEncode + StrictDecode + StrictDumb,
+{
+    /// Exports the strict-encoded map of closed seals to the messages they
+    /// are closed over.
+    pub fn export_closed_seals(&self) -> Vec<u8> { export_closed(&self.closed) }
+
+    /// Imports the set of closed seals previously exported with
+    /// [`Self::export_closed_seals`], replacing the current one. If the data
+    /// are invalid, errors with [`MemorySealError::InvalidState`] leaving the
+    /// medium unchanged.
+    pub fn import_closed_seals(&mut self, data: &[u8]) -> Result<(), MemorySealError> {
+        self.closed = import_closed(data).map_err(|_| MemorySealError::InvalidState)?;
+        Ok(())
+    }
+}
+
+impl<Seal: Ord, Msg: Eq> SealProtocol<Seal> for MemorySeals<Seal, Msg> {
+    type Witness = MemoryWitness<Seal, Msg>;
+    type Message = Msg;
+    type PublicationId = ();
+    type Error = MemorySealError;
+
+    fn get_seal_status(&self, seal: &Seal) -> Result<SealStatus, MemorySealError> {
+        Ok(match self.closed.contains_key(seal) {
+            true => SealStatus::Closed,
+            false => SealStatus::Open,
+        })
+    }
+}
+
+impl<Seal: Ord + Clone, Msg: Eq + Clone> CloseSeal<Seal> for MemorySeals<Seal, Msg> {
+    fn close_seal(
+        &mut self,
+        seal: &Seal,
+        over: &Msg,
+    ) -> Result<MemoryWitness<Seal, Msg>, MemorySealError> {
+        self.close_all_seals([seal], over)
+    }
+
+    fn check_closable(&self, seal: &Seal) -> Result<(), MemorySealError> {
+        match self.closed.contains_key(seal) {
+            true => Err(MemorySealError::AlreadyClosed),
+            false => Ok(()),
+        }
+    }
+
+    fn close_all_seals<'seal>(
+        &mut self,
+        seals: impl IntoIterator<Item = &'seal Seal>,
+        over: &Msg,
+    ) -> Result<MemoryWitness<Seal, Msg>, MemorySealError>
+    where
+        Seal: 'seal,
+    {
+        let seals = seals.into_iter().cloned().collect::<BTreeSet<_>>();
+        for seal in &seals {
+            self.check_closable(seal)?;
+        }
+        for seal in &seals {
+            self.closed.insert(seal.clone(), over.clone());
+        }
+        Ok(MemoryWitness {
+            seals,
+            msg: over.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{SealCloseError, SealMediumError};
+
+    #[test]
+    fn close_verify() {
+        let mut medium = MemorySeals::<u16, &str>::new();
+        assert_eq!(medium.get_seal_status(&1), Ok(SealStatus::Open));
+
+        let witness = medium.close_seal(&1, &"message").unwrap();
+        assert_eq!(medium.get_seal_status(&1), Ok(SealStatus::Closed));
+        assert_eq!(medium.closed_over(&1), Some(&"message"));
+        assert_eq!(witness.verify_seal(&1, &"message"), Ok(()));
+        assert_eq!(witness.verify_seal(&1, &"other"), Err(MemorySealError::NotClosed));
+        assert_eq!(witness.verify_seal(&2, &"message"), Err(MemorySealError::NotClosed));
+
+        assert_eq!(medium.close_seal(&1, &"other"), Err(MemorySealError::AlreadyClosed));
+        assert!(matches!(medium.export_state(), Err(SealMediumError::SnapshotNotSupported)));
+    }
+
+    #[test]
+    fn close_all() {
+        let mut medium = MemorySeals::<u16, u64>::new();
+        let witness = medium.close_all_seals([&3, &1, &2], &0xcafe).unwrap();
+        assert_eq!(witness.verify_many_seals([&1, &2, &3], &0xcafe), Ok(()));
+
+        assert_eq!(medium.close_all_seals([&4, &2], &0xbeef), Err(MemorySealError::AlreadyClosed));
+        assert_eq!(medium.get_seal_status(&4), Ok(SealStatus::Open));
+    }
+
+    #[test]
+    fn close_indexed_failure() {
+        let mut medium = MemorySeals::<u16, u64>::new();
+        medium.close_seal(&3, &0xcafe).unwrap();
+
+        assert_eq!(
+            medium.close_all_seals_indexed([&1, &2, &3, &4], &0xbeef),
+            Err(SealCloseError {
+                index: Some(2),
+                source: MemorySealError::AlreadyClosed
+            })
+        );
+        for seal in [1, 2, 4] {
+            assert_eq!(medium.get_seal_status(&seal), Ok(SealStatus::Open));
+            assert_eq!(medium.closed_over(&seal), None);
+        }
+
+        let witness = medium
+            .close_all_seals_indexed([&1, &2, &4], &0xbeef)
+            .unwrap();
+        assert_eq!(witness.verify_many_seals([&1, &2, &4], &0xbeef), Ok(()));
+    }
+
+    #[test]
+    fn export_import_state() {
+        let mut medium = MemorySeals::<u16, u64>::new();
+        medium.close_all_seals([&1, &2], &0xcafe).unwrap();
+        medium.close_seal(&5, &0xbeef).unwrap();
+
+        let data = medium.export_closed_seals();
+        let mut restored = MemorySeals::<u16, u64>::new();
+        restored.import_closed_seals(&data).unwrap();
+        assert_eq!(restored, medium);
+        for seal in 0..8 {
+            assert_eq!(restored.get_seal_status(&seal), medium.get_seal_status(&seal));
+            assert_eq!(restored.closed_over(&seal), medium.closed_over(&seal));
+        }
+        assert_eq!(restored.close_seal(&5, &0xcafe), Err(MemorySealError::AlreadyClosed));
+
+        assert_eq!(restored.import_closed_seals(&data[1..]), Err(MemorySealError::InvalidState));
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert_eq!(restored.import_closed_seals(&trailing), Err(MemorySealError::InvalidState));
+        assert_eq!(restored, medium);
+    }
+}